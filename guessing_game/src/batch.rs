@@ -0,0 +1,135 @@
+//! Plays scripted games without anyone at the keyboard, for test harnesses.
+//!
+//! A script is the lines a player would have typed, one guess per line. A line of `---` starts the
+//! next game, lines starting with `#` are comments and blank lines are skipped. Once a game is
+//! over, the rest of its lines are ignored.
+//!
+//! The games run against a clock that never moves, so time limits can't go off and the same
+//! script with the same seed always comes out the same.
+use crate::clock::ManualClock;
+use crate::config::Config;
+use crate::game::Game;
+use crate::round::{Round, Secret};
+use crate::transcript::GameResult;
+use serde::Serialize;
+use std::io::{self, BufRead};
+
+/// Separates one game's guesses from the next.
+pub const MARKER: &str = "---";
+
+/// How one scripted game went, printed as a line of JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// Counting from 1, in the order the games appear.
+    pub game: usize,
+    pub seed: u64,
+    pub result: GameResult,
+    pub attempts: u32,
+    pub secret: Secret,
+    /// Lines after the game was over, which didn't get played.
+    pub unused: usize,
+}
+
+/// Splits a script into games, each a list of lines to play.
+pub fn games<R: BufRead>(reader: R) -> io::Result<Vec<Vec<String>>> {
+    let mut games = vec![Vec::new()];
+
+    for line in reader.lines() {
+        let line = line?;
+        let text = line.trim();
+
+        if text == MARKER {
+            games.push(Vec::new());
+        } else if !text.is_empty() && !text.starts_with('#') {
+            if let Some(game) = games.last_mut() {
+                game.push(line);
+            }
+        }
+    }
+
+    // a marker at the very start or end doesn't make an empty game
+    games.retain(|game| !game.is_empty());
+    Ok(games)
+}
+
+/// Plays `lines` as game number `game`, with the secret picked from `seed`.
+pub fn play(config: &Config, game: usize, seed: u64, lines: &[String]) -> Summary {
+    let mut round = Game::seeded(config, seed).with_clock(Box::new(ManualClock::new()));
+
+    let mut played = 0;
+    for line in lines {
+        if round.is_over() {
+            break;
+        }
+        // rejections are part of the game, they just don't change the summary
+        let _ = round.answer(line);
+        played += 1;
+    }
+
+    Summary {
+        game,
+        seed,
+        result: GameResult::of(&round),
+        attempts: Round::attempts(&round),
+        secret: Round::secret(&round),
+        unused: lines.len() - played,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn markers_split_the_script_into_games() {
+        let script = "# warm up\n---\n50\n\n25\n  ---  \n# nothing here\n---\n10\n x \n---\n";
+
+        let split = games(script.as_bytes()).unwrap();
+        assert_eq!(split, [lines(&["50", "25"]), lines(&["10", " x "])]);
+
+        assert!(games("".as_bytes()).unwrap().is_empty());
+        assert_eq!(games("7".as_bytes()).unwrap(), [lines(&["7"])]);
+    }
+
+    #[test]
+    fn lines_after_the_game_is_over_are_unused() {
+        let config = Config::default();
+        let counting: Vec<String> = (1..=100).map(|n| n.to_string()).collect();
+
+        let summary = play(&config, 3, 9, &counting);
+        let Secret::Number(secret) = summary.secret else {
+            panic!("the number game's secret is a number")
+        };
+        assert_eq!(summary.game, 3);
+        assert_eq!(summary.result, GameResult::Won);
+        assert_eq!(summary.attempts as i64, secret);
+        assert_eq!(summary.unused as i64, 100 - secret);
+    }
+
+    #[test]
+    fn running_out_of_lines_abandons_the_game() {
+        let config = Config::default();
+        let summary = play(&config, 1, 9, &lines(&["oops", "0"]));
+
+        assert_eq!(summary.result, GameResult::Abandoned);
+        assert_eq!(summary.attempts, 0);
+        assert_eq!(summary.unused, 0);
+    }
+
+    #[test]
+    fn the_seed_decides_the_game() {
+        let config = Config::default();
+        let guesses = lines(&["50", "25", "75", "12", "88"]);
+
+        assert_eq!(play(&config, 1, 5, &guesses), play(&config, 1, 5, &guesses));
+
+        let secrets: std::collections::HashSet<_> = (0..20)
+            .map(|seed| play(&config, 1, seed, &guesses).secret.to_string())
+            .collect();
+        assert!(secrets.len() > 1);
+    }
+}
@@ -0,0 +1,234 @@
+//! Bulls and cows: guess a code of distinct digits. A bull is a right digit in the right place,
+//! a cow is a right digit in the wrong place.
+use crate::clock::Clock;
+use crate::config::Config;
+use crate::input::Rejection;
+use crate::messages::{Key, Messages};
+use crate::round::{Answer, GameKind, Progress, Round, Secret};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// What a code is made of, 10 digits means a code can be at most 10 long.
+const DIGITS: [u8; 10] = [b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9'];
+
+#[derive(Debug)]
+pub struct BullsAndCows {
+    code: Vec<u8>,
+    progress: Progress,
+    history: Vec<(String, Result<Answer, Rejection>)>,
+}
+
+impl BullsAndCows {
+    /// `code` has to be distinct ASCII digits.
+    pub fn new(config: &Config, code: &str) -> BullsAndCows {
+        assert!(
+            parse_code(code, code.len()).is_ok(),
+            "'{code}' isn't a code of distinct digits"
+        );
+
+        BullsAndCows {
+            code: code.as_bytes().to_vec(),
+            progress: Progress::new(config),
+            history: Vec::new(),
+        }
+    }
+
+    /// A random code of `digits` distinct digits, 1 to 10 of them.
+    pub fn seeded(config: &Config, digits: usize, seed: u64) -> BullsAndCows {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut shuffled = DIGITS;
+        shuffled.shuffle(&mut rng);
+
+        let code = String::from_utf8_lossy(&shuffled[..digits.clamp(1, DIGITS.len())]).to_string();
+        BullsAndCows::new(config, &code)
+    }
+
+    /// Times the game against `clock` instead of the system's.
+    pub fn with_clock(self, clock: Box<dyn Clock>) -> BullsAndCows {
+        BullsAndCows {
+            progress: self.progress.with_clock(clock),
+            ..self
+        }
+    }
+
+    pub fn digits(&self) -> usize {
+        self.code.len()
+    }
+
+    /// Every line played so far and what it got back.
+    pub fn history(&self) -> &[(String, Result<Answer, Rejection>)] {
+        &self.history
+    }
+
+    fn score(&self, guess: &[u8]) -> Answer {
+        let bulls = guess.iter().zip(&self.code).filter(|(g, c)| g == c).count() as u32;
+        // digits are distinct, so every shared digit is either a bull or a cow
+        let shared = guess.iter().filter(|g| self.code.contains(g)).count() as u32;
+
+        Answer::Code {
+            bulls,
+            cows: shared - bulls,
+        }
+    }
+}
+
+/// Checks a guess is exactly `digits` distinct digits.
+pub fn parse_code(line: &str, digits: usize) -> Result<Vec<u8>, Rejection> {
+    let text = line.trim();
+
+    if text.is_empty() {
+        return Err(Rejection::Empty);
+    }
+    if !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Rejection::NotANumber(text.to_string()));
+    }
+    if text.len() != digits {
+        return Err(Rejection::WrongLength {
+            got: text.len(),
+            expected: digits,
+        });
+    }
+
+    let code = text.as_bytes().to_vec();
+    for (i, digit) in code.iter().enumerate() {
+        if code[..i].contains(digit) {
+            return Err(Rejection::RepeatedDigit(*digit as char));
+        }
+    }
+
+    Ok(code)
+}
+
+impl Round for BullsAndCows {
+    fn kind(&self) -> GameKind {
+        GameKind::Bulls
+    }
+
+    fn answer(&mut self, line: &str) -> Result<Answer, Rejection> {
+        let result = self
+            .progress
+            .start_turn()
+            .and_then(|()| parse_code(line, self.code.len()))
+            .map(|guess| self.score(&guess));
+
+        match result {
+            Ok(Answer::Code { bulls, .. }) => {
+                self.progress.guessed(bulls as usize == self.code.len());
+            }
+            Ok(_) => unreachable!("bulls and cows only answers with codes"),
+            Err(_) => self.progress.rejected(),
+        }
+
+        self.history.push((line.trim().to_string(), result.clone()));
+        result
+    }
+
+    fn feedback(&self, answer: &Answer, messages: &Messages) -> String {
+        match self.is_won() {
+            true => messages.get(Key::YouWin).to_string(),
+            false => messages.answer(answer),
+        }
+    }
+
+    fn prompt(&self, messages: &Messages) -> String {
+        messages.format(Key::OfDigits, &[("digits", &self.code.len())])
+    }
+
+    // 10 choices for the first digit, 9 for the second and so on
+    fn possibilities(&self) -> f64 {
+        (0..self.code.len())
+            .map(|i| (DIGITS.len() - i) as f64)
+            .product()
+    }
+
+    fn secret(&self) -> Secret {
+        Secret::Code(String::from_utf8_lossy(&self.code).to_string())
+    }
+
+    fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    fn progress_mut(&mut self) -> &mut Progress {
+        &mut self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(bulls: u32, cows: u32) -> Result<Answer, Rejection> {
+        Ok(Answer::Code { bulls, cows })
+    }
+
+    #[test]
+    fn bulls_and_cows_are_counted() {
+        let mut game = BullsAndCows::new(&Config::default(), "1234");
+
+        assert_eq!(game.answer("5678"), code(0, 0));
+        assert_eq!(game.answer("4321"), code(0, 4));
+        assert_eq!(game.answer("1243"), code(2, 2));
+        assert_eq!(game.answer("1567"), code(1, 0));
+        assert_eq!(game.answer("9120"), code(0, 2));
+        assert!(!game.is_over());
+
+        assert_eq!(game.answer("1234\n"), code(4, 0));
+        assert!(game.is_won());
+        assert_eq!(game.attempts(), 6);
+    }
+
+    #[test]
+    fn a_code_has_to_be_the_right_number_of_distinct_digits() {
+        assert_eq!(parse_code(" 0123\n", 4), Ok(b"0123".to_vec()));
+
+        assert_eq!(parse_code("  ", 4), Err(Rejection::Empty));
+        assert_eq!(
+            parse_code("123", 4),
+            Err(Rejection::WrongLength {
+                got: 3,
+                expected: 4
+            })
+        );
+        assert_eq!(
+            parse_code("12345", 4),
+            Err(Rejection::WrongLength {
+                got: 5,
+                expected: 4
+            })
+        );
+        assert_eq!(parse_code("1231", 4), Err(Rejection::RepeatedDigit('1')));
+        assert_eq!(
+            parse_code("12a4", 4),
+            Err(Rejection::NotANumber(String::from("12a4")))
+        );
+        assert_eq!(
+            parse_code("-123", 4),
+            Err(Rejection::NotANumber(String::from("-123")))
+        );
+    }
+
+    #[test]
+    fn rejected_codes_dont_cost_an_attempt() {
+        let mut game = BullsAndCows::new(&Config::default(), "1234");
+
+        assert!(game.answer("1123").is_err());
+        assert!(game.answer("12").is_err());
+        assert_eq!(game.attempts(), 0);
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn a_seeded_code_is_distinct_digits_of_the_right_length() {
+        for seed in 0..50 {
+            let game = BullsAndCows::seeded(&Config::default(), 4, seed);
+            let Secret::Code(code) = game.secret() else {
+                panic!("a bulls and cows secret is a code")
+            };
+            assert_eq!(parse_code(&code, 4).map(|c| c.len()), Ok(4));
+        }
+
+        assert_eq!(BullsAndCows::seeded(&Config::default(), 99, 1).digits(), 10);
+    }
+}
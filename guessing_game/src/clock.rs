@@ -0,0 +1,175 @@
+//! Time limits. The clock is a trait so the limits can be checked against a clock that only
+//! moves when it's told to, instead of sleeping.
+use crate::messages::Messages;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Somewhere to read the time from. Only differences between readings mean anything.
+pub trait Clock: fmt::Debug + Send {
+    fn now(&self) -> Duration;
+}
+
+/// The real thing.
+#[derive(Debug)]
+pub struct SystemClock(Instant);
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// A clock that stands still until it's moved. Clones share the same time, so one can be handed
+/// to a [`Timer`] and the other kept to move it along.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.0.store(now.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+/// Which limit ran out, and what it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Timeout {
+    Guess(Duration),
+    Game(Duration),
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().timeout(*self))
+    }
+}
+
+/// `m:ss`, for prompts and messages. Partial seconds round up, so a countdown only shows 0:00
+/// once it's really run out.
+pub fn clock(duration: Duration) -> String {
+    let secs = duration.as_millis().div_ceil(1000);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// `12.34s`, for times that are compared against each other.
+pub fn seconds(duration: Duration) -> String {
+    format!("{:.2}s", duration.as_secs_f64())
+}
+
+/// Keeps time for one game: how long it's been going, and how long the current guess has.
+#[derive(Debug)]
+pub struct Timer {
+    clock: Box<dyn Clock>,
+    per_guess: Option<Duration>,
+    per_game: Option<Duration>,
+    game_started: Duration,
+    guess_started: Duration,
+    /// When the game ended, after which the timer stands still.
+    stopped: Option<Duration>,
+}
+
+impl Timer {
+    /// Starts timing straight away.
+    pub fn new(
+        clock: Box<dyn Clock>,
+        per_guess: Option<Duration>,
+        per_game: Option<Duration>,
+    ) -> Timer {
+        let now = clock.now();
+
+        Timer {
+            clock,
+            per_guess,
+            per_game,
+            game_started: now,
+            guess_started: now,
+            stopped: None,
+        }
+    }
+
+    /// The per-guess and per-game limits.
+    pub fn limits(&self) -> (Option<Duration>, Option<Duration>) {
+        (self.per_guess, self.per_game)
+    }
+
+    fn now(&self) -> Duration {
+        self.stopped.unwrap_or_else(|| self.clock.now())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.now().saturating_sub(self.game_started)
+    }
+
+    /// The clock for the next guess starts now.
+    pub fn restart_guess(&mut self) {
+        self.guess_started = self.now();
+    }
+
+    /// Stops the clock for good, the game is over.
+    pub fn stop(&mut self) {
+        self.stopped = Some(self.now());
+    }
+
+    /// Time until whichever limit runs out first, `None` if there aren't any.
+    pub fn time_left(&self) -> Option<Duration> {
+        let now = self.now();
+        let guess = self
+            .per_guess
+            .map(|limit| (self.guess_started + limit).saturating_sub(now));
+        let game = self
+            .per_game
+            .map(|limit| (self.game_started + limit).saturating_sub(now));
+
+        match (guess, game) {
+            (Some(guess), Some(game)) => Some(guess.min(game)),
+            (left, None) | (None, left) => left,
+        }
+    }
+
+    /// The limit that's run out, if one has. The whole game running out wins over the one guess.
+    pub fn expired(&self) -> Option<Timeout> {
+        let now = self.now();
+
+        if let Some(limit) = self.per_game {
+            if now >= self.game_started + limit {
+                return Some(Timeout::Game(limit));
+            }
+        }
+        if let Some(limit) = self.per_guess {
+            if now >= self.guess_started + limit {
+                return Some(Timeout::Guess(limit));
+            }
+        }
+
+        None
+    }
+}
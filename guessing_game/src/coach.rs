@@ -0,0 +1,160 @@
+//! Coach mode: how much each guess told the player, measured in bits, next to what guessing the
+//! middle of what's left would have told them.
+//!
+//! Halving the candidates is worth one bit, and finding a secret among `n` numbers takes
+//! `log2(n)` of them, which is why binary search is the yardstick.
+use crate::config::Range;
+use crate::game::Turn;
+use crate::solver;
+
+/// What one guess was worth.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Advice {
+    /// Numbers still possible before and after the guess.
+    pub before: u128,
+    pub after: u128,
+    /// Bits the guess gained.
+    pub gained: f64,
+    /// The middle of what was possible, and the bits it would have gained at worst.
+    pub midpoint: i64,
+    pub best: f64,
+}
+
+impl Advice {
+    /// `before` and `after` are the possible numbers either side of the guess, as
+    /// [`crate::Game::feasible`] has them.
+    pub fn new(before: (i64, i64), after: (i64, i64)) -> Advice {
+        let size = |(low, high): (i64, i64)| (high as i128 - low as i128 + 1) as u128;
+        let (n, after_size) = (size(before), size(after));
+
+        Advice {
+            before: n,
+            after: after_size,
+            gained: bits(n, after_size),
+            midpoint: (before.0 as i128 + (n as i128 - 1) / 2) as i64,
+            // the midpoint leaves the bigger half at worst, and the secret at best
+            best: bits(n, (n / 2).max(1)),
+        }
+    }
+}
+
+fn bits(before: u128, after: u128) -> f64 {
+    (before as f64 / after.max(1) as f64).log2()
+}
+
+/// How a whole game measured up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub attempts: u32,
+    /// The most guesses binary search could need on the range.
+    pub binary_search: u32,
+    pub gained: f64,
+    pub best: f64,
+}
+
+impl Report {
+    /// Goes over the guesses in `history`, skipping input that wasn't a guess.
+    pub fn new(range: Range, history: &[Turn], feasible: impl Fn(&[Turn]) -> (i64, i64)) -> Report {
+        let mut report = Report {
+            attempts: 0,
+            binary_search: solver::binary_search_bound(range),
+            gained: 0.0,
+            best: 0.0,
+        };
+
+        for (i, turn) in history.iter().enumerate() {
+            if let Turn::Guess(..) = turn {
+                let advice = Advice::new(feasible(&history[..i]), feasible(&history[..=i]));
+                report.attempts += 1;
+                report.gained += advice.gained;
+                report.best += advice.best;
+            }
+        }
+
+        report
+    }
+
+    /// The bits gained as a share of what midpoint guesses would have gained, as a percentage.
+    /// A lucky guess can take it over 100.
+    pub fn efficiency(&self) -> f64 {
+        match self.best > 0.0 {
+            true => 100.0 * self.gained / self.best,
+            false => 100.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::game::Game;
+    use crate::round::Round;
+
+    #[test]
+    fn halving_the_range_is_worth_a_bit() {
+        let advice = Advice::new((1, 100), (51, 100));
+
+        assert_eq!((advice.before, advice.after), (100, 50));
+        assert_eq!(advice.gained, 1.0);
+        assert_eq!(advice.midpoint, 50);
+        assert_eq!(advice.best, 1.0);
+    }
+
+    #[test]
+    fn an_edge_guess_gains_little_and_a_win_gains_everything() {
+        let edge = Advice::new((1, 100), (2, 100));
+        assert!(edge.gained > 0.0 && edge.gained < 0.02, "{edge:?}");
+
+        let win = Advice::new((1, 100), (42, 42));
+        assert_eq!(win.gained, 100f64.log2());
+        assert!(win.gained > win.best);
+    }
+
+    #[test]
+    fn the_midpoint_is_found_on_any_range() {
+        assert_eq!(Advice::new((1, 3), (1, 1)).midpoint, 2);
+        assert_eq!(Advice::new((7, 7), (7, 7)).midpoint, 7);
+
+        let everything = Advice::new((i64::MIN, i64::MAX), (0, i64::MAX));
+        assert_eq!(everything.midpoint, -1);
+        assert_eq!(everything.gained, 1.0);
+        assert_eq!(everything.best, 1.0);
+    }
+
+    #[test]
+    fn efficiency_is_gained_over_best() {
+        let report = |gained, best| Report {
+            attempts: 3,
+            binary_search: 7,
+            gained,
+            best,
+        };
+
+        assert_eq!(report(3.0, 6.0).efficiency(), 50.0);
+        assert_eq!(report(6.0, 6.0).efficiency(), 100.0);
+        // nothing to learn, so nothing was wasted
+        assert_eq!(report(0.0, 0.0).efficiency(), 100.0);
+    }
+
+    #[test]
+    fn a_game_is_reported_on_guess_by_guess() {
+        let config = Config {
+            coach: true,
+            ..Config::default()
+        };
+        let mut game = Game::new(&config, 42);
+        for line in ["50", "not a guess", "25", "37", "43", "40", "42"] {
+            let _ = game.answer(line);
+        }
+
+        let report = game.coach_report().unwrap();
+        assert_eq!(report.attempts, 6);
+        assert_eq!(report.binary_search, 7);
+        // won outright, so every one of the 100 numbers' bits was gained
+        assert!((report.gained - 100f64.log2()).abs() < 1e-9, "{report:?}");
+        assert!(report.efficiency() > 90.0, "{report:?}");
+
+        assert_eq!(Game::new(&Config::default(), 42).coach_report(), None);
+    }
+}
@@ -0,0 +1,532 @@
+use crate::hints::HintPolicy;
+use crate::messages::{Locale, Messages};
+use crate::score::SPEEDRUN;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// An inclusive range the secret is picked from, e.g. `1..=100`. Serialises as `[low, high]`,
+/// checked on the way back in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "(i64, i64)", into = "(i64, i64)")]
+pub struct Range {
+    low: i64,
+    high: i64,
+}
+
+impl Range {
+    pub fn new(low: i64, high: i64) -> Result<Range, ConfigError> {
+        // an inclusive range with low == high is a single number, which is a silly game but fine
+        if low > high {
+            return Err(ConfigError::InvertedRange { low, high });
+        }
+
+        Ok(Range { low, high })
+    }
+
+    pub fn low(&self) -> i64 {
+        self.low
+    }
+
+    pub fn high(&self) -> i64 {
+        self.high
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        (self.low..=self.high).contains(&n)
+    }
+
+    /// How many numbers are in the range. `i64::MIN..=i64::MAX` doesn't fit in a `u64`, hence `u128`.
+    pub fn size(&self) -> u128 {
+        (self.high as i128 - self.low as i128 + 1) as u128
+    }
+}
+
+impl TryFrom<(i64, i64)> for Range {
+    type Error = ConfigError;
+
+    fn try_from((low, high): (i64, i64)) -> Result<Range, ConfigError> {
+        Range::new(low, high)
+    }
+}
+
+impl From<Range> for (i64, i64) {
+    fn from(range: Range) -> (i64, i64) {
+        (range.low, range.high)
+    }
+}
+
+impl Default for Range {
+    fn default() -> Range {
+        Range { low: 1, high: 100 }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().range(*self))
+    }
+}
+
+/// Named presets that set both the range and how many guesses you get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn range(&self) -> Range {
+        match self {
+            Difficulty::Easy => Range { low: 1, high: 10 },
+            Difficulty::Normal => Range { low: 1, high: 100 },
+            Difficulty::Hard => Range { low: 1, high: 1000 },
+        }
+    }
+
+    // enough to always win with a binary search, give or take
+    pub fn max_attempts(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 7,
+            Difficulty::Hard => 10,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Difficulty, ConfigError> {
+        match name.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(ConfigError::UnknownDifficulty(name.to_string())),
+        }
+    }
+}
+
+/// Everything the command line can change about a game.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Config {
+    pub range: Range,
+    pub max_attempts: Option<u32>,
+    pub difficulty: Option<Difficulty>,
+    pub seed: Option<u64>,
+    /// Whether input that isn't a valid guess still uses up an attempt.
+    pub count_rejected: bool,
+    /// Who the high scores are recorded against.
+    pub player: Option<String>,
+    pub scores_path: Option<PathBuf>,
+    /// Where every finished game is logged for `stats`.
+    pub stats_path: Option<PathBuf>,
+    pub hints: HintPolicy,
+    /// Say how much each guess was worth, in bits.
+    pub coach: bool,
+    /// Where to log the game as JSON lines, see [`crate::transcript`].
+    pub transcript_path: Option<PathBuf>,
+    /// Don't pick the secret up front, dodge every guess for as long as possible.
+    pub evil: bool,
+    /// Liar mode: how many "too small"/"too big" answers may be wrong.
+    pub lies: u32,
+    /// Full-screen view instead of the line-by-line one.
+    pub tui: bool,
+    /// Time limits, running out of either loses the game.
+    pub guess_time: Option<Duration>,
+    pub game_time: Option<Duration>,
+    /// Rank high scores by how long the game took rather than how many guesses.
+    pub speedrun: bool,
+    /// Which language to talk to the player in, `LANG` decides when this isn't set.
+    pub lang: Option<Locale>,
+}
+
+impl Config {
+    /// Reads options from the command line, minus the program name.
+    ///
+    /// `--difficulty` sets the range and attempt limit first, then `--min`/`--max` override the
+    /// range, so `--difficulty hard --max 500` is hard's attempt limit on `1..=500`.
+    pub fn from_args<I>(args: I) -> Result<Config, ConfigError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut difficulty = None;
+        let mut low = None;
+        let mut high = None;
+        let mut seed = None;
+        let mut count_rejected = false;
+        let mut max_attempts = None;
+        let mut player = None;
+        let mut scores_path = None;
+        let mut stats_path = None;
+        let mut hints = HintPolicy::Off;
+        let mut transcript_path = None;
+        let mut evil = false;
+        let mut coach = false;
+        let mut lies = 0;
+        let mut tui = false;
+        let mut guess_time = None;
+        let mut game_time = None;
+        let mut speedrun = false;
+        let mut lang = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--difficulty" | "-d" => {
+                    difficulty = Some(Difficulty::parse(&value(&arg, args.next())?)?);
+                }
+                "--min" => low = Some(number(&arg, value(&arg, args.next())?)?),
+                "--max" => high = Some(number(&arg, value(&arg, args.next())?)?),
+                "--seed" => seed = Some(number(&arg, value(&arg, args.next())?)?),
+                "--count-rejected" => count_rejected = true,
+                "--evil" => evil = true,
+                "--coach" => coach = true,
+                "--tui" => tui = true,
+                "--guess-time" => guess_time = Some(seconds(&arg, args.next())?),
+                "--game-time" => game_time = Some(seconds(&arg, args.next())?),
+                "--speedrun" => speedrun = true,
+                "--lies" => lies = number(&arg, value(&arg, args.next())?)?,
+                "--max-attempts" => {
+                    let max = number(&arg, value(&arg, args.next())?)?;
+                    if max == 0 {
+                        return Err(ConfigError::NoAttempts);
+                    }
+                    max_attempts = Some(max);
+                }
+                "--player" => player = Some(value(&arg, args.next())?),
+                "--scores" => scores_path = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--stats" => stats_path = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--lang" => lang = Some(Locale::parse(&value(&arg, args.next())?)?),
+                "--hints" => hints = HintPolicy::parse(&value(&arg, args.next())?)?,
+                "--transcript" => transcript_path = Some(PathBuf::from(value(&arg, args.next())?)),
+                _ => return Err(ConfigError::UnknownOption(arg)),
+            }
+        }
+
+        // the evil host picks its answers to stay consistent, lying on top of that breaks it
+        if evil && lies > 0 {
+            return Err(ConfigError::Conflict("--evil", "--lies"));
+        }
+        // hints are worked out from the real secret, so they'd give the lies away
+        if lies > 0 && hints != HintPolicy::Off {
+            return Err(ConfigError::Conflict("--lies", "--hints"));
+        }
+        // and the evil host doesn't have a real secret, it moves it next to every guess
+        if evil && hints != HintPolicy::Off {
+            return Err(ConfigError::Conflict("--evil", "--hints"));
+        }
+
+        let mut config = Config {
+            seed,
+            count_rejected,
+            player,
+            scores_path,
+            stats_path,
+            hints,
+            coach,
+            transcript_path,
+            evil,
+            lies,
+            tui,
+            guess_time,
+            game_time,
+            speedrun,
+            lang,
+            ..Config::default()
+        };
+
+        if let Some(difficulty) = difficulty {
+            config.range = difficulty.range();
+            config.max_attempts = Some(difficulty.max_attempts());
+            config.difficulty = Some(difficulty);
+        }
+
+        config.range = Range::new(
+            low.unwrap_or(config.range.low),
+            high.unwrap_or(config.range.high),
+        )?;
+
+        if max_attempts.is_some() {
+            config.max_attempts = max_attempts;
+        }
+
+        Ok(config)
+    }
+
+    /// Whether the clock matters to this game: there's a time limit, or it's a speed run. Only
+    /// then are times shown, so the same seed and input print the same thing every time.
+    pub fn timed(&self) -> bool {
+        self.speedrun || self.guess_time.is_some() || self.game_time.is_some()
+    }
+
+    /// Fails on the first option `game` can't honour. The evil host, lies, hints, the coach and
+    /// the full-screen view only exist for the classic number game.
+    pub fn check_plain(&self, game: &'static str) -> Result<(), ConfigError> {
+        let options = [
+            ("--evil", self.evil),
+            ("--lies", self.lies > 0),
+            ("--hints", self.hints != HintPolicy::Off),
+            ("--coach", self.coach),
+            ("--tui", self.tui),
+        ];
+
+        match options.into_iter().find(|(_, set)| *set) {
+            Some((option, _)) => Err(ConfigError::Unsupported { option, game }),
+            None => Ok(()),
+        }
+    }
+
+    /// What high scores are filed under: the preset's name, or the range for anything custom.
+    pub fn label(&self) -> String {
+        let label = match self.difficulty {
+            Some(difficulty) if difficulty.range() == self.range => difficulty.name().to_string(),
+            _ => format!("{}..={}", self.range.low, self.range.high),
+        };
+
+        self.filed_under(label)
+    }
+
+    /// `label` with [`SPEEDRUN`] on the end for a speed run, so the other games' tables are kept
+    /// apart the same way.
+    pub fn filed_under(&self, label: String) -> String {
+        match self.speedrun {
+            true => label + SPEEDRUN,
+            false => label,
+        }
+    }
+}
+
+/// Pulls `name` and its value out of `args`, leaving the rest for [`Config::from_args`]. This is
+/// how subcommands pick off their own options.
+pub fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, ConfigError> {
+    let Some(i) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+
+    args.remove(i);
+    if i >= args.len() {
+        return Err(ConfigError::MissingValue(name.to_string()));
+    }
+
+    Ok(Some(args.remove(i)))
+}
+
+/// Like [`take_option`] for options that are numbers.
+pub fn take_number<T: std::str::FromStr>(
+    args: &mut Vec<String>,
+    name: &str,
+) -> Result<Option<T>, ConfigError> {
+    take_option(args, name)?
+        .map(|value| number(name, value))
+        .transpose()
+}
+
+// seconds as a decimal, so `--guess-time 2.5` works
+fn seconds(option: &str, value: Option<String>) -> Result<Duration, ConfigError> {
+    let value = self::value(option, value)?;
+
+    match value.trim().parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(ConfigError::InvalidNumber {
+            option: option.to_string(),
+            value,
+        }),
+    }
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, ConfigError> {
+    value.ok_or_else(|| ConfigError::MissingValue(option.to_string()))
+}
+
+fn number<T: std::str::FromStr>(option: &str, value: String) -> Result<T, ConfigError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError::InvalidNumber {
+            option: option.to_string(),
+            value,
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    InvertedRange {
+        low: i64,
+        high: i64,
+    },
+    UnknownDifficulty(String),
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidNumber {
+        option: String,
+        value: String,
+    },
+    NoAttempts,
+    UnknownStrategy(String),
+    UnknownHintPolicy(String),
+    SecretOutOfRange {
+        secret: i64,
+        range: Range,
+    },
+    /// Two options that don't make sense together.
+    Conflict(&'static str, &'static str),
+    /// An option the game being played has no use for.
+    Unsupported {
+        option: &'static str,
+        game: &'static str,
+    },
+    UnknownLocale(String),
+    UnknownFormat(String),
+    UnknownNumberType(String),
+    /// A count that has to be in `low..=high`.
+    NotBetween {
+        option: &'static str,
+        low: usize,
+        high: usize,
+    },
+    /// [`ConfigError::InvertedRange`] for numbers that aren't `i64`s.
+    EmptyRange {
+        low: String,
+        high: String,
+    },
+    /// A decimal range too wide to pick a secret from.
+    TooWide {
+        low: String,
+        high: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().config_error(self))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Config, ConfigError> {
+        Config::from_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn no_options_is_1_to_100_with_no_limit() {
+        let config = parse("").unwrap();
+        assert_eq!(config.range, Range::new(1, 100).unwrap());
+        assert_eq!(config.max_attempts, None);
+        assert_eq!(config.label(), "1..=100");
+    }
+
+    #[test]
+    fn the_preset_goes_first_and_options_override_it() {
+        let config = parse("--difficulty hard --max 500").unwrap();
+        assert_eq!(config.range, Range::new(1, 500).unwrap());
+        assert_eq!(config.max_attempts, Some(10));
+        // not hard's range any more, so it isn't filed under hard
+        assert_eq!(config.label(), "1..=500");
+
+        // the order on the command line doesn't matter
+        assert_eq!(
+            parse("--max-attempts 3 --min 5 -d easy").map(|c| (c.range, c.max_attempts)),
+            Ok((Range::new(5, 10).unwrap(), Some(3)))
+        );
+        assert_eq!(
+            parse("-d Hard --speedrun").unwrap().label(),
+            "hard speedrun"
+        );
+    }
+
+    #[test]
+    fn an_inverted_range_is_an_error() {
+        assert_eq!(
+            parse("--min 10 --max 1"),
+            Err(ConfigError::InvertedRange { low: 10, high: 1 })
+        );
+        assert_eq!(
+            parse("-d easy --min 11"),
+            Err(ConfigError::InvertedRange { low: 11, high: 10 })
+        );
+        assert!(parse("--min 7 --max 7").is_ok());
+    }
+
+    #[test]
+    fn bad_numbers_and_missing_values_are_errors() {
+        assert_eq!(parse("--max-attempts 0"), Err(ConfigError::NoAttempts));
+        assert_eq!(
+            parse("--max lots"),
+            Err(ConfigError::InvalidNumber {
+                option: String::from("--max"),
+                value: String::from("lots")
+            })
+        );
+        assert_eq!(
+            parse("--guess-time -1"),
+            Err(ConfigError::InvalidNumber {
+                option: String::from("--guess-time"),
+                value: String::from("-1")
+            })
+        );
+        assert_eq!(
+            parse("--seed"),
+            Err(ConfigError::MissingValue(String::from("--seed")))
+        );
+        assert_eq!(
+            parse("--colour"),
+            Err(ConfigError::UnknownOption(String::from("--colour")))
+        );
+    }
+
+    #[test]
+    fn options_that_dont_mix_are_refused() {
+        assert_eq!(
+            parse("--evil --lies 1"),
+            Err(ConfigError::Conflict("--evil", "--lies"))
+        );
+        assert_eq!(
+            parse("--lies 2 --hints bands"),
+            Err(ConfigError::Conflict("--lies", "--hints"))
+        );
+        assert_eq!(
+            parse("--hints trend --evil"),
+            Err(ConfigError::Conflict("--evil", "--hints"))
+        );
+        assert!(parse("--evil --hints off").is_ok());
+
+        assert_eq!(
+            parse("--coach").unwrap().check_plain("bulls"),
+            Err(ConfigError::Unsupported {
+                option: "--coach",
+                game: "bulls"
+            })
+        );
+        assert_eq!(parse("--seed 3").unwrap().check_plain("bulls"), Ok(()));
+    }
+
+    #[test]
+    fn options_can_be_taken_out_first() {
+        let mut args: Vec<String> = ["--games", "12", "--max", "50"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(take_number::<u32>(&mut args, "--games"), Ok(Some(12)));
+        assert_eq!(take_number::<u32>(&mut args, "--games"), Ok(None));
+        assert_eq!(args, ["--max", "50"]);
+
+        let mut args = vec![String::from("--strategy")];
+        assert_eq!(
+            take_option(&mut args, "--strategy"),
+            Err(ConfigError::MissingValue(String::from("--strategy")))
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Several secrets at once, and the round isn't over until every one of them has been found.
+//!
+//! A guess is answered either for the nearest secret that's still hidden, which plays like the
+//! classic game a secret at a time, or for every secret, smallest first.
+use crate::clock::Clock;
+use crate::config::{Config, Range};
+use crate::game::Outcome;
+use crate::input::{self, Rejection};
+use crate::messages::{Key, Messages};
+use crate::round::{Answer, GameKind, Progress, Round, Secret};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// The most secrets a game can have. Any more and nobody's finding them all anyway.
+pub const MAX_SECRETS: usize = 1000;
+
+#[derive(Debug)]
+pub struct FindAll {
+    /// Distinct and in order, smallest first.
+    secrets: Vec<i64>,
+    found: Vec<bool>,
+    /// Answer for every secret rather than the nearest.
+    each: bool,
+    range: Range,
+    progress: Progress,
+    history: Vec<(String, Result<Answer, Rejection>)>,
+}
+
+impl FindAll {
+    pub fn new(config: &Config, mut secrets: Vec<i64>, each: bool) -> FindAll {
+        secrets.sort_unstable();
+        secrets.dedup();
+        assert!(!secrets.is_empty(), "there has to be a secret to find");
+        assert!(
+            secrets.iter().all(|s| config.range.contains(*s)),
+            "secrets {secrets:?} aren't all {}",
+            config.range
+        );
+
+        FindAll {
+            found: vec![false; secrets.len()],
+            secrets,
+            each,
+            range: config.range,
+            progress: Progress::new(config),
+            history: Vec::new(),
+        }
+    }
+
+    /// `count` different secrets from the configured range, or as many as fit, and never more
+    /// than [`MAX_SECRETS`].
+    pub fn seeded(config: &Config, count: usize, each: bool, seed: u64) -> FindAll {
+        let range = config.range;
+        let count = (count.min(MAX_SECRETS) as u128).min(range.size()) as usize;
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // drawn until there are enough different ones, in draw order so a seed always gives the
+        // same secrets
+        let mut seen = HashSet::with_capacity(count);
+        let mut secrets = Vec::with_capacity(count);
+        while secrets.len() < count {
+            let secret = rng.gen_range(range.low()..=range.high());
+            if seen.insert(secret) {
+                secrets.push(secret);
+            }
+        }
+
+        FindAll::new(config, secrets, each)
+    }
+
+    /// Times the game against `clock` instead of the system's.
+    pub fn with_clock(self, clock: Box<dyn Clock>) -> FindAll {
+        FindAll {
+            progress: self.progress.with_clock(clock),
+            ..self
+        }
+    }
+
+    /// Answers `guess`, marking off any secret it hits.
+    pub fn guess(&mut self, guess: i64) -> Answer {
+        // the secret to answer for is picked before this guess marks anything off
+        let nearest = self.nearest(guess);
+        if let Some(i) = self.secrets.iter().position(|s| *s == guess) {
+            self.found[i] = true;
+        }
+        self.progress.guessed(self.found.iter().all(|found| *found));
+
+        match self.each {
+            true => Answer::Each(
+                self.secrets
+                    .iter()
+                    .map(|secret| Outcome::from(guess.cmp(secret)))
+                    .collect(),
+            ),
+            // guessing one that's already been found is answered for the nearest that hasn't
+            false => Answer::Nearest(match nearest {
+                Some(secret) => Outcome::from(guess.cmp(&secret)),
+                None => Outcome::Win,
+            }),
+        }
+    }
+
+    // the closest secret still hidden, the smaller one if two are as close
+    fn nearest(&self, guess: i64) -> Option<i64> {
+        self.secrets
+            .iter()
+            .zip(&self.found)
+            .filter(|(_, found)| !**found)
+            .map(|(secret, _)| *secret)
+            .min_by_key(|secret| secret.abs_diff(guess))
+    }
+
+    pub fn secrets(&self) -> &[i64] {
+        &self.secrets
+    }
+
+    pub fn found(&self) -> usize {
+        self.found.iter().filter(|found| **found).count()
+    }
+
+    pub fn history(&self) -> &[(String, Result<Answer, Rejection>)] {
+        &self.history
+    }
+}
+
+impl Round for FindAll {
+    fn kind(&self) -> GameKind {
+        match self.each {
+            true => GameKind::FindEach,
+            false => GameKind::FindAll,
+        }
+    }
+
+    fn answer(&mut self, line: &str) -> Result<Answer, Rejection> {
+        let result = self
+            .progress
+            .start_turn()
+            .and_then(|()| input::parse_guess(line, self.range))
+            .map(|guess| self.guess(guess));
+
+        if result.is_err() {
+            self.progress.rejected();
+        }
+
+        self.history.push((line.trim().to_string(), result.clone()));
+        result
+    }
+
+    fn feedback(&self, answer: &Answer, messages: &Messages) -> String {
+        let found = messages.format(
+            Key::FoundSoFar,
+            &[("found", &self.found()), ("total", &self.secrets.len())],
+        );
+
+        format!("{}\n{found}", messages.answer(answer))
+    }
+
+    fn prompt(&self, messages: &Messages) -> String {
+        messages.range(self.range)
+    }
+
+    // every way of picking that many numbers from the range, n choose k
+    fn possibilities(&self) -> f64 {
+        let n = self.range.size() as f64;
+        (0..self.secrets.len())
+            .map(|i| (n - i as f64) / (i + 1) as f64)
+            .product()
+    }
+
+    fn secret(&self) -> Secret {
+        Secret::Many(self.secrets.clone())
+    }
+
+    fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    fn progress_mut(&mut self) -> &mut Progress {
+        &mut self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(low: i64, high: i64) -> Config {
+        Config {
+            range: Range::new(low, high).unwrap(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn no_more_secrets_than_the_range_holds() {
+        let game = FindAll::seeded(&config(1, 5), 10, false, 1);
+        assert_eq!(game.secrets(), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn a_huge_count_is_capped() {
+        let game = FindAll::seeded(&config(i64::MIN, i64::MAX), usize::MAX, false, 1);
+        assert_eq!(game.secrets().len(), MAX_SECRETS);
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_secrets() {
+        let first = FindAll::seeded(&config(1, 1000), 50, false, 7);
+        let second = FindAll::seeded(&config(1, 1000), 50, false, 7);
+
+        assert_eq!(first.secrets(), second.secrets());
+        assert_eq!(first.secrets().len(), 50);
+    }
+}
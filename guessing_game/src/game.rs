@@ -0,0 +1,636 @@
+use crate::clock::{Clock, Timeout};
+use crate::coach::{self, Advice};
+use crate::config::{Config, Range};
+use crate::hints::{Hint, HintPolicy};
+use crate::input::{self, Rejection};
+use crate::messages::{Key, Messages};
+use crate::number;
+use crate::round::{Answer, GameKind, Progress, Round, Secret};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How often liar mode lies, while it still has lies left.
+const LIE_CHANCE: f64 = 0.3;
+
+/// What the game says back after a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    TooSmall,
+    TooBig,
+    Win,
+}
+
+impl From<Ordering> for Outcome {
+    // `guess.cmp(&secret)` is the whole game, this just gives the arms a name
+    fn from(ordering: Ordering) -> Outcome {
+        match ordering {
+            Ordering::Less => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+            Ordering::Equal => Outcome::Win,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(Messages::default().outcome(*self))
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = ();
+
+    /// The reverse of `Display`, for reading outcomes back off the wire.
+    fn from_str(s: &str) -> Result<Outcome, ()> {
+        [Outcome::TooSmall, Outcome::TooBig, Outcome::Win]
+            .into_iter()
+            .find(|outcome| outcome.to_string() == s)
+            .ok_or(())
+    }
+}
+
+/// One line of the game's history: a guess and its answer, or input that wasn't a guess at all.
+/// The guess is an `i64` in the classic game, see [`crate::number`] for the others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Turn<N = i64> {
+    Guess(N, Outcome),
+    Rejected(String, Rejection),
+}
+
+impl<N: fmt::Display> fmt::Display for Turn<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().turn(self))
+    }
+}
+
+/// A single round: the secret, how many guesses have been made, and what each one got back.
+#[derive(Debug)]
+pub struct Game {
+    secret: i64,
+    range: Range,
+    hints: HintPolicy,
+    coach: bool,
+    /// Evil mode: every number still consistent with the answers so far. `secret` is kept
+    /// somewhere inside it but isn't really decided until there's only one left.
+    candidates: Option<(i64, i64)>,
+    /// Liar mode: how many wrong answers the host may still give, and which history entries
+    /// already were.
+    max_lies: u32,
+    lies: Vec<usize>,
+    rng: StdRng,
+    progress: Progress,
+    history: Vec<Turn>,
+}
+
+impl Game {
+    pub fn new(config: &Config, secret: i64) -> Game {
+        assert!(
+            config.range.contains(secret),
+            "secret {secret} is not {}",
+            config.range
+        );
+
+        Game {
+            secret,
+            range: config.range,
+            hints: config.hints,
+            coach: config.coach,
+            candidates: config
+                .evil
+                .then(|| (config.range.low(), config.range.high())),
+            max_lies: config.lies,
+            lies: Vec::new(),
+            rng: config
+                .seed
+                .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
+            progress: Progress::new(config),
+            history: Vec::new(),
+        }
+    }
+
+    /// A secret picked at random from the configured range, `1..=100` unless told otherwise.
+    pub fn random(config: &Config) -> Game {
+        Game::seeded(config, rand::random())
+    }
+
+    /// Same seed, same range, same secret, so a game can be replayed exactly.
+    pub fn seeded(config: &Config, seed: u64) -> Game {
+        let range = config.range;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let secret = rng.gen_range(range.low()..=range.high());
+
+        // lies come from the same stream, so they replay too
+        Game {
+            rng,
+            ..Game::new(config, secret)
+        }
+    }
+
+    /// Times the game against `clock` instead of the system's, restarting the timer.
+    pub fn with_clock(self, clock: Box<dyn Clock>) -> Game {
+        Game {
+            progress: self.progress.with_clock(clock),
+            ..self
+        }
+    }
+
+    pub fn guess(&mut self, guess: i64) -> Outcome {
+        let outcome = match self.candidates {
+            Some(candidates) => self.evil_answer(guess, candidates),
+            None => number::outcome(guess, self.secret, 0),
+        };
+        let outcome = self.maybe_lie(outcome);
+
+        self.history.push(Turn::Guess(guess, outcome));
+        self.progress.guessed(outcome == Outcome::Win);
+
+        outcome
+    }
+
+    /// Says whichever of "too small" or "too big" leaves more numbers in play, and only admits
+    /// a win when the guess is the last number left.
+    fn evil_answer(&mut self, guess: i64, (low, high): (i64, i64)) -> Outcome {
+        let (outcome, low, high) = if guess < low {
+            (Outcome::TooSmall, low, high)
+        } else if guess > high {
+            (Outcome::TooBig, low, high)
+        } else if low == high {
+            (Outcome::Win, low, high)
+        } else if high as i128 - guess as i128 >= guess as i128 - low as i128 {
+            (Outcome::TooSmall, guess + 1, high)
+        } else {
+            (Outcome::TooBig, low, guess - 1)
+        };
+
+        // keep a secret that agrees with everything said so far, so the game can end at any point
+        self.candidates = Some((low, high));
+        self.secret = self.secret.clamp(low, high);
+
+        outcome
+    }
+
+    /// In liar mode, swaps "too small" and "too big" now and again until the lies run out. A win
+    /// is never lied about.
+    fn maybe_lie(&mut self, outcome: Outcome) -> Outcome {
+        if outcome == Outcome::Win
+            || self.lies.len() as u32 >= self.max_lies
+            || !self.rng.gen_bool(LIE_CHANCE)
+        {
+            return outcome;
+        }
+
+        // the history entry this answer is about to become
+        self.lies.push(self.history.len());
+
+        match outcome {
+            Outcome::TooSmall => Outcome::TooBig,
+            _ => Outcome::TooSmall,
+        }
+    }
+
+    /// Checks every answer in the history against the secret, allowing for the lies liar mode
+    /// owns up to. In evil mode it proves the host never went back on its word. Returns the first
+    /// guess that disagrees.
+    pub fn verify(&self) -> Result<(), i64> {
+        for (i, turn) in self.history.iter().enumerate() {
+            if let Turn::Guess(guess, outcome) = turn {
+                let truthful = number::outcome(*guess, self.secret, 0) == *outcome;
+                if truthful == self.lies.contains(&i) {
+                    return Err(*guess);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Positions in [`Game::history`] where the host lied.
+    pub fn lies(&self) -> &[usize] {
+        &self.lies
+    }
+
+    pub fn max_lies(&self) -> u32 {
+        self.max_lies
+    }
+
+    pub fn is_evil(&self) -> bool {
+        self.candidates.is_some()
+    }
+
+    /// Takes a raw line of input rather than a number. Anything that isn't a guess inside the
+    /// range is kept in the history, and only costs an attempt if `count_rejected` is set.
+    ///
+    /// A line that arrives after the time limit is rejected too, and the game is lost.
+    pub fn submit(&mut self, line: &str) -> Result<Outcome, Rejection> {
+        let result = self
+            .progress
+            .start_turn()
+            .and_then(|()| input::parse_guess(line, self.range));
+
+        match result {
+            Ok(guess) => Ok(self.guess(guess)),
+            Err(rejection) => {
+                self.history
+                    .push(Turn::Rejected(line.trim().to_string(), rejection.clone()));
+                self.progress.rejected();
+                Err(rejection)
+            }
+        }
+    }
+
+    pub fn secret(&self) -> i64 {
+        self.secret
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.progress.attempts()
+    }
+
+    pub fn history(&self) -> &[Turn] {
+        &self.history
+    }
+
+    /// `None` when there's no limit.
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.progress.attempts_left()
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.progress.is_won()
+    }
+
+    /// Warmer/colder hints for the latest guess, if they're turned on and it wasn't a win.
+    pub fn hint(&self) -> Option<Hint> {
+        if self.hints == HintPolicy::Off || self.is_won() {
+            return None;
+        }
+
+        let mut guesses = self.history.iter().rev().filter_map(|turn| match turn {
+            Turn::Guess(guess, _) => Some(*guess),
+            Turn::Rejected(..) => None,
+        });
+        let guess = guesses.next()?;
+
+        Some(Hint::new(
+            self.hints,
+            guess,
+            guesses.next(),
+            self.secret,
+            self.range,
+        ))
+    }
+
+    /// What's left of the range going by the answers so far, the part a careful player would
+    /// still be guessing in. A lie can leave nothing, in which case it's the whole range again.
+    pub fn feasible(&self) -> (i64, i64) {
+        self.feasible_after(&self.history)
+    }
+
+    // `feasible` as of the end of `turns`, which is a start of the history
+    fn feasible_after(&self, turns: &[Turn]) -> (i64, i64) {
+        let (mut low, mut high) = (self.range.low(), self.range.high());
+
+        for turn in turns {
+            match turn {
+                Turn::Guess(guess, Outcome::TooSmall) => low = low.max(guess.saturating_add(1)),
+                Turn::Guess(guess, Outcome::TooBig) => high = high.min(guess.saturating_sub(1)),
+                Turn::Guess(guess, Outcome::Win) => (low, high) = (*guess, *guess),
+                Turn::Rejected(..) => {}
+            }
+        }
+
+        match low <= high {
+            true => (low, high),
+            false => (self.range.low(), self.range.high()),
+        }
+    }
+
+    /// In coach mode, what the latest guess was worth.
+    pub fn advice(&self) -> Option<Advice> {
+        if !self.coach {
+            return None;
+        }
+
+        let last = self
+            .history
+            .iter()
+            .rposition(|turn| matches!(turn, Turn::Guess(..)))?;
+        Some(Advice::new(
+            self.feasible_after(&self.history[..last]),
+            self.feasible_after(&self.history[..=last]),
+        ))
+    }
+
+    /// In coach mode, how the game so far measures up against binary search.
+    pub fn coach_report(&self) -> Option<coach::Report> {
+        self.coach.then(|| {
+            coach::Report::new(self.range, &self.history, |turns| {
+                self.feasible_after(turns)
+            })
+        })
+    }
+
+    /// See [`Progress::check_time`].
+    pub fn check_time(&mut self) -> Option<Timeout> {
+        self.progress.check_time()
+    }
+
+    /// How long until a time limit runs out, if there are any.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.progress.time_left()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.progress.elapsed()
+    }
+
+    pub fn timed_out(&self) -> Option<Timeout> {
+        self.progress.timed_out()
+    }
+
+    /// Out of guesses or out of time without having found the secret.
+    pub fn is_lost(&self) -> bool {
+        self.progress.is_lost()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.progress.is_over()
+    }
+
+    /// Points for the game so far, nothing unless it's been won.
+    pub fn score(&self) -> u32 {
+        Round::score(self)
+    }
+}
+
+impl Round for Game {
+    fn kind(&self) -> GameKind {
+        GameKind::Number
+    }
+
+    fn answer(&mut self, line: &str) -> Result<Answer, Rejection> {
+        self.submit(line).map(Answer::Number)
+    }
+
+    fn feedback(&self, answer: &Answer, messages: &Messages) -> String {
+        let mut feedback = match answer {
+            Answer::Number(Outcome::Win) => messages.answer(answer),
+            _ => messages.format(
+                Key::SecretIs,
+                &[
+                    ("outcome", &messages.answer(answer)),
+                    ("range", &messages.range(self.range)),
+                ],
+            ),
+        };
+
+        if let Some(hint) = self.hint() {
+            feedback.push_str(&format!("\n{}", messages.hint(hint)));
+        }
+        if let Some(advice) = self.advice() {
+            let won = *answer == Answer::Number(Outcome::Win);
+            feedback.push_str(&format!("\n{}", messages.advice(&advice, won)));
+        }
+
+        feedback
+    }
+
+    fn prompt(&self, messages: &Messages) -> String {
+        messages.range(self.range)
+    }
+
+    fn secret(&self) -> Secret {
+        Secret::Number(self.secret)
+    }
+
+    fn possibilities(&self) -> f64 {
+        self.range.size() as f64
+    }
+
+    fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    fn progress_mut(&mut self) -> &mut Progress {
+        &mut self.progress
+    }
+
+    fn lies(&self) -> &[usize] {
+        &self.lies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    // a game on 1..=100 with the secret 50, and the clock it's timed against
+    fn timed(guess_time: Option<u32>, game_time: Option<u32>) -> (Game, ManualClock) {
+        let config = Config {
+            guess_time: guess_time.map(|secs| SECOND * secs),
+            game_time: game_time.map(|secs| SECOND * secs),
+            ..Config::default()
+        };
+        let clock = ManualClock::new();
+
+        (
+            Game::new(&config, 50).with_clock(Box::new(clock.clone())),
+            clock,
+        )
+    }
+
+    fn config(max_attempts: Option<u32>) -> Config {
+        Config {
+            max_attempts,
+            ..Config::default()
+        }
+    }
+
+    // a binary search to the end, whatever the host says
+    fn bisect(game: &mut Game) {
+        let (mut low, mut high) = (game.range().low(), game.range().high());
+        while !game.is_over() {
+            let guess = low + (high - low) / 2;
+            match game.guess(guess) {
+                Outcome::TooSmall => low = guess + 1,
+                Outcome::TooBig => high = guess - 1,
+                Outcome::Win => {}
+            }
+        }
+    }
+
+    #[test]
+    fn too_small_too_big_and_a_win() {
+        let mut game = Game::new(&config(None), 50);
+
+        assert_eq!(game.guess(25), Outcome::TooSmall);
+        assert_eq!(game.guess(75), Outcome::TooBig);
+        assert!(!game.is_over());
+        assert_eq!(game.guess(50), Outcome::Win);
+
+        assert!(game.is_won() && game.is_over() && !game.is_lost());
+        assert_eq!(game.attempts(), 3);
+        assert_eq!(
+            game.history(),
+            [
+                Turn::Guess(25, Outcome::TooSmall),
+                Turn::Guess(75, Outcome::TooBig),
+                Turn::Guess(50, Outcome::Win),
+            ]
+        );
+    }
+
+    #[test]
+    fn running_out_of_attempts_loses() {
+        let mut game = Game::new(&config(Some(2)), 50);
+
+        game.guess(1);
+        assert_eq!(game.attempts_left(), Some(1));
+        game.guess(2);
+
+        assert!(game.is_lost() && game.is_over());
+        assert_eq!(game.attempts_left(), Some(0));
+    }
+
+    #[test]
+    fn rejected_input_is_free_unless_it_counts() {
+        let mut game = Game::new(&config(Some(2)), 50);
+        for line in ["", "abc", "500", "-3"] {
+            assert!(game.submit(line).is_err());
+        }
+        assert_eq!(game.attempts(), 0);
+        assert_eq!(game.history().len(), 4);
+        assert!(!game.is_over());
+
+        let counted = Config {
+            count_rejected: true,
+            ..config(Some(2))
+        };
+        let mut game = Game::new(&counted, 50);
+        assert!(game.submit("abc").is_err());
+        assert!(game.submit("500").is_err());
+        assert_eq!(game.attempts(), 2);
+        assert!(game.is_lost());
+    }
+
+    #[test]
+    fn the_same_seed_picks_the_same_secret() {
+        let config = Config {
+            range: Range::new(-1000, 1000).unwrap(),
+            ..Config::default()
+        };
+
+        for seed in 0..50 {
+            let secret = Game::seeded(&config, seed).secret();
+            assert_eq!(Game::seeded(&config, seed).secret(), secret);
+            assert!(config.range.contains(secret));
+        }
+    }
+
+    #[test]
+    fn the_evil_host_never_goes_back_on_its_word() {
+        let config = Config {
+            evil: true,
+            ..config(None)
+        };
+        let mut game = Game::new(&config, 1);
+        bisect(&mut game);
+
+        assert!(game.is_evil() && game.is_won());
+        // evil mode dodges the first guess that would have won, so bisecting 1..=100 takes all 7
+        assert_eq!(game.attempts(), 7);
+        assert_eq!(game.verify(), Ok(()));
+    }
+
+    #[test]
+    fn every_lie_is_owned_up_to() {
+        let config = Config {
+            lies: 2,
+            ..config(None)
+        };
+
+        let mut lied = 0;
+        for seed in 0..20 {
+            let mut game = Game::seeded(&config, seed);
+            let mut guess = 0;
+            while !game.is_won() {
+                guess += 1;
+                game.guess(guess);
+            }
+
+            assert!(game.lies().len() <= 2);
+            assert_eq!(game.verify(), Ok(()));
+            lied += game.lies().len();
+        }
+        assert!(lied > 0, "nobody lied, so nothing was checked");
+    }
+
+    #[test]
+    fn each_guess_gets_the_full_time() {
+        let (mut game, clock) = timed(Some(10), None);
+
+        clock.advance(SECOND * 9);
+        assert_eq!(game.submit("25"), Ok(Outcome::TooSmall));
+        assert_eq!(game.time_left(), Some(SECOND * 10));
+
+        clock.advance(SECOND * 9);
+        assert_eq!(game.submit("75"), Ok(Outcome::TooBig));
+        assert_eq!(game.check_time(), None);
+    }
+
+    #[test]
+    fn a_slow_guess_loses() {
+        let (mut game, clock) = timed(Some(10), None);
+        let timeout = Timeout::Guess(SECOND * 10);
+
+        clock.advance(SECOND * 10);
+        assert_eq!(game.submit("50"), Err(Rejection::TimedOut(timeout)));
+        assert!(game.is_lost());
+        assert_eq!(game.timed_out(), Some(timeout));
+        // the winning number doesn't count once time's up
+        assert_eq!(game.submit("50"), Err(Rejection::TimedOut(timeout)));
+        assert!(!game.is_won());
+    }
+
+    #[test]
+    fn the_whole_game_runs_out_before_the_guess_does() {
+        let (mut game, clock) = timed(Some(10), Some(15));
+
+        clock.advance(SECOND * 8);
+        assert_eq!(game.submit("25"), Ok(Outcome::TooSmall));
+        clock.advance(SECOND * 5);
+        assert_eq!(game.time_left(), Some(SECOND * 2));
+        assert_eq!(game.check_time(), None);
+
+        clock.advance(SECOND * 2);
+        assert_eq!(game.check_time(), Some(Timeout::Game(SECOND * 15)));
+        assert!(game.is_lost());
+        assert_eq!(game.elapsed(), SECOND * 15);
+    }
+
+    #[test]
+    fn the_clock_stops_when_the_game_is_won() {
+        let (mut game, clock) = timed(Some(10), Some(15));
+
+        clock.advance(SECOND * 3);
+        assert_eq!(game.submit("50"), Ok(Outcome::Win));
+        clock.advance(SECOND * 100);
+
+        assert_eq!(game.check_time(), None);
+        assert_eq!(game.elapsed(), SECOND * 3);
+        assert!(game.is_won());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::config::{ConfigError, Range};
+
+/// How close a guess landed, relative to the size of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Band {
+    Burning,
+    Hot,
+    Warm,
+    Cold,
+}
+
+impl Band {
+    /// Burning is within 2% of the range, hot 10%, warm 25%, everything else is cold. A miss by
+    /// one is always burning, otherwise nothing would ever burn on `1..=10`.
+    pub fn new(distance: u128, range: Range) -> Band {
+        let size = range.size();
+        let within = |percent: u128| distance <= (size * percent / 100).max(1);
+
+        if within(2) {
+            Band::Burning
+        } else if within(10) {
+            Band::Hot
+        } else if within(25) {
+            Band::Warm
+        } else {
+            Band::Cold
+        }
+    }
+}
+
+/// Whether a guess got closer to the secret than the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Warmer,
+    Colder,
+    Same,
+}
+
+/// Which hints to give on top of "Too small!"/"Too big!".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HintPolicy {
+    #[default]
+    Off,
+    Bands,
+    Trend,
+    Both,
+}
+
+impl HintPolicy {
+    pub fn parse(name: &str) -> Result<HintPolicy, ConfigError> {
+        match name {
+            "off" => Ok(HintPolicy::Off),
+            "bands" => Ok(HintPolicy::Bands),
+            "trend" => Ok(HintPolicy::Trend),
+            "both" => Ok(HintPolicy::Both),
+            _ => Err(ConfigError::UnknownHintPolicy(name.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    pub band: Option<Band>,
+    /// `None` on the first guess, there's nothing to compare against.
+    pub trend: Option<Trend>,
+}
+
+impl Hint {
+    pub fn new(
+        policy: HintPolicy,
+        guess: i64,
+        previous: Option<i64>,
+        secret: i64,
+        range: Range,
+    ) -> Hint {
+        let distance = |n: i64| (n as i128 - secret as i128).unsigned_abs();
+
+        let band = match policy {
+            HintPolicy::Bands | HintPolicy::Both => Some(Band::new(distance(guess), range)),
+            _ => None,
+        };
+        let trend = match policy {
+            HintPolicy::Trend | HintPolicy::Both => {
+                previous.map(|previous| match distance(guess).cmp(&distance(previous)) {
+                    std::cmp::Ordering::Less => Trend::Warmer,
+                    std::cmp::Ordering::Greater => Trend::Colder,
+                    std::cmp::Ordering::Equal => Trend::Same,
+                })
+            }
+            _ => None,
+        };
+
+        Hint { band, trend }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(low: i64, high: i64) -> Range {
+        Range::new(low, high).unwrap()
+    }
+
+    #[test]
+    fn bands_scale_with_the_range() {
+        let hundred = range(1, 100);
+        assert_eq!(Band::new(2, hundred), Band::Burning);
+        assert_eq!(Band::new(3, hundred), Band::Hot);
+        assert_eq!(Band::new(10, hundred), Band::Hot);
+        assert_eq!(Band::new(25, hundred), Band::Warm);
+        assert_eq!(Band::new(26, hundred), Band::Cold);
+
+        let wide = range(1, 10_000);
+        assert_eq!(Band::new(200, wide), Band::Burning);
+        assert_eq!(Band::new(201, wide), Band::Hot);
+        assert_eq!(Band::new(2_501, wide), Band::Cold);
+
+        let everything = range(i64::MIN, i64::MAX);
+        assert_eq!(Band::new(u64::MAX as u128, everything), Band::Cold);
+        assert_eq!(Band::new(1 << 50, everything), Band::Burning);
+    }
+
+    #[test]
+    fn a_miss_by_one_always_burns() {
+        assert_eq!(Band::new(1, range(1, 10)), Band::Burning);
+        assert_eq!(Band::new(2, range(1, 10)), Band::Warm);
+        assert_eq!(Band::new(1, range(0, 1)), Band::Burning);
+    }
+
+    #[test]
+    fn the_trend_compares_distances_not_directions() {
+        let trend = |guess, previous| {
+            Hint::new(HintPolicy::Trend, guess, previous, 50, range(1, 100)).trend
+        };
+
+        assert_eq!(trend(40, None), None);
+        assert_eq!(trend(45, Some(40)), Some(Trend::Warmer));
+        assert_eq!(trend(70, Some(40)), Some(Trend::Colder));
+        // just as far away, on the other side
+        assert_eq!(trend(60, Some(40)), Some(Trend::Same));
+        assert_eq!(trend(55, Some(40)), Some(Trend::Warmer));
+    }
+
+    #[test]
+    fn the_policy_picks_which_hints_are_given() {
+        let hint = |policy| Hint::new(policy, 49, Some(40), 50, range(1, 100));
+
+        assert_eq!(
+            hint(HintPolicy::Off),
+            Hint {
+                band: None,
+                trend: None
+            }
+        );
+        assert_eq!(hint(HintPolicy::Bands).band, Some(Band::Burning));
+        assert_eq!(hint(HintPolicy::Bands).trend, None);
+        assert_eq!(hint(HintPolicy::Trend).band, None);
+        assert_eq!(
+            hint(HintPolicy::Both),
+            Hint {
+                band: Some(Band::Burning),
+                trend: Some(Trend::Warmer)
+            }
+        );
+        assert!(HintPolicy::parse("sometimes").is_err());
+    }
+}
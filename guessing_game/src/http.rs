@@ -0,0 +1,576 @@
+//! The number game as a JSON API over HTTP, for playing from a browser, a script or `curl`.
+//!
+//! - `POST /games` with `{"min": 1, "max": 100, "seed": 42, "max_attempts": 7}`, every field
+//!   optional, starts a game and returns its state, including the `id` to use from then on
+//! - `GET /games/{id}` returns the state: the range, attempts, history, and the secret and seed
+//!   once it's over
+//! - `POST /games/{id}/guesses` with `{"guess": "50"}` plays a guess and returns the outcome
+//!   (`too_small`, `too_big` or `win`) or why it wasn't a guess, plus the new state
+//! - `DELETE /games/{id}` throws a game away
+//!
+//! Errors come back as `{"error": "..."}` with a 4xx status. It's plain HTTP/1.1 over std's
+//! `TcpListener`, one request per connection and a thread each, which is plenty for a game. At
+//! most [`MAX_GAMES`] are kept, see there for what happens after that.
+use crate::config::{Config, Range};
+use crate::game::{Game, Outcome, Turn};
+use crate::messages::{Key, Locale, Messages};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long a connection gets to send its request, so a stuck client doesn't hold a thread.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Requests are a handful of numbers, anything this big is a mistake.
+const MAX_BODY: usize = 64 * 1024;
+/// How many games are kept at once. Finished games make way for new ones, oldest first, but once
+/// this many are still being played `POST /games` is refused until one ends or is deleted.
+pub const MAX_GAMES: usize = 1000;
+
+/// The body of `POST /games`. Anything left out comes from the server's own options.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewGame {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+}
+
+/// Everything a client can know about a game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameState {
+    pub id: u64,
+    pub low: i64,
+    pub high: i64,
+    /// Only given away once the game is over, it's as good as the secret.
+    pub seed: Option<u64>,
+    pub attempts: u32,
+    /// `None` when there's no limit.
+    pub attempts_left: Option<u32>,
+    pub won: bool,
+    pub over: bool,
+    /// Only given away once the game is over.
+    pub secret: Option<i64>,
+    pub history: Vec<Entry>,
+}
+
+/// One line of a game's history, [`Turn`] in JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Entry {
+    Guess { guess: i64, outcome: Outcome },
+    Rejected { input: String, rejected: String },
+}
+
+/// The body of `POST /games/{id}/guesses`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuessRequest {
+    /// A number, or a string so that rubbish gets the same answer it would at the terminal
+    /// rather than a JSON error.
+    pub guess: serde_json::Value,
+}
+
+/// What a guess got back. Exactly one of `outcome` and `rejected` is set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuessReply {
+    pub outcome: Option<Outcome>,
+    pub rejected: Option<String>,
+    pub game: GameState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// A game and the seed it was started with, so a client can report it.
+struct Running {
+    game: Game,
+    seed: u64,
+}
+
+/// Every game the server is running, by id.
+#[derive(Default)]
+struct Games {
+    games: HashMap<u64, Running>,
+    next_id: u64,
+}
+
+/// Answers requests on `listener` until the process is killed. `config` is the starting point
+/// for every new game: its range, attempt limit, hints and so on, which a request can override.
+/// With a `--seed`, game `n` is seeded with it plus `n` unless the request has a seed of its own,
+/// so each game is different but the whole run can be played again.
+pub fn serve(listener: TcpListener, config: Config) -> io::Result<()> {
+    let games = Arc::new(Mutex::new(Games::default()));
+    let messages = Messages::new(config.lang.unwrap_or_else(Locale::from_env));
+    let warn = move |warning: &dyn std::fmt::Display| {
+        eprintln!("{}", messages.format(Key::Warning, &[("warning", warning)]));
+    };
+    let config = Arc::new(config);
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            // one bad connection shouldn't take the server down with it
+            Err(err) => {
+                warn(&messages.format(Key::CouldntAccept, &[("err", &err)]));
+                continue;
+            }
+        };
+
+        let games = Arc::clone(&games);
+        let config = Arc::clone(&config);
+        thread::spawn(move || {
+            if let Err(err) = connection(stream, &games, &config) {
+                warn(&err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn json<T: Serialize>(status: u16, body: &T) -> Response {
+        Response {
+            status,
+            body: serde_json::to_string(body).unwrap_or_default(),
+        }
+    }
+
+    fn error(status: u16, error: impl Into<String>) -> Response {
+        Response::json(
+            status,
+            &ErrorBody {
+                error: error.into(),
+            },
+        )
+    }
+}
+
+fn connection(stream: TcpStream, games: &Mutex<Games>, config: &Config) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader) {
+        Ok(request) => route(&request, games, config),
+        Err(err) if err.kind() == ErrorKind::InvalidData => Response::error(400, err.to_string()),
+        Err(err) => return Err(err),
+    };
+
+    write_response(stream, &response)
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Request> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(bad_request("malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(bad_request("request ended in the headers"));
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request("bad Content-Length"))?;
+            }
+        }
+    }
+
+    if length > MAX_BODY {
+        return Err(bad_request("request body too large"));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request { method, path, body })
+}
+
+fn bad_request(why: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, why)
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        429 => "Too Many Requests",
+        _ => "",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        response.status,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+fn route(request: &Request, games: &Mutex<Games>, config: &Config) -> Response {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    // a thread that panicked mid-request leaves the games as they were, they're still usable
+    let mut games = games
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["games"]) => create(&request.body, &mut games, config),
+        ("GET", ["games", id]) => match find(&games, id) {
+            Some((id, entry)) => Response::json(200, &state(id, entry)),
+            None => not_found(id),
+        },
+        ("DELETE", ["games", id]) => match id.parse().ok().and_then(|id| games.games.remove(&id)) {
+            Some(_) => Response::json(200, &serde_json::json!({})),
+            None => not_found(id),
+        },
+        ("POST", ["games", id, "guesses"]) => guess(&request.body, &mut games, id),
+        (_, ["games"]) | (_, ["games", _]) | (_, ["games", _, "guesses"]) => {
+            Response::error(405, format!("{} isn't allowed here", request.method))
+        }
+        _ => Response::error(404, format!("nothing at {path}")),
+    }
+}
+
+fn create(body: &[u8], games: &mut Games, config: &Config) -> Response {
+    let new: NewGame = match body {
+        [] => NewGame::default(),
+        body => match serde_json::from_slice(body) {
+            Ok(new) => new,
+            Err(err) => return Response::error(400, err.to_string()),
+        },
+    };
+
+    let mut config = config.clone();
+    config.range = match Range::new(
+        new.min.unwrap_or(config.range.low()),
+        new.max.unwrap_or(config.range.high()),
+    ) {
+        Ok(range) => range,
+        Err(err) => return Response::error(400, err.to_string()),
+    };
+    match new.max_attempts {
+        Some(0) => return Response::error(400, "max_attempts must be at least 1"),
+        Some(max) => config.max_attempts = Some(max),
+        None => {}
+    }
+
+    if games.games.len() >= MAX_GAMES {
+        // a finished game is only kept so its result can be looked up, so it can go
+        let oldest = games
+            .games
+            .iter()
+            .filter(|(_, entry)| entry.game.is_over())
+            .map(|(id, _)| *id)
+            .min();
+        match oldest {
+            Some(id) => games.games.remove(&id),
+            None => return Response::error(429, "too many games in play, finish or delete one"),
+        };
+    }
+
+    games.next_id += 1;
+    let id = games.next_id;
+
+    let seed = new
+        .seed
+        .or(config.seed.map(|seed| seed.wrapping_add(id)))
+        .unwrap_or_else(rand::random);
+    let game = Game::seeded(&config, seed);
+    let entry = games.games.entry(id).or_insert(Running { game, seed });
+
+    Response::json(201, &state(id, entry))
+}
+
+fn guess(body: &[u8], games: &mut Games, id: &str) -> Response {
+    let request: GuessRequest = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(err) => return Response::error(400, err.to_string()),
+    };
+    let Some((id, entry)) = id
+        .parse()
+        .ok()
+        .and_then(|n| Some((n, games.games.get_mut(&n)?)))
+    else {
+        return not_found(id);
+    };
+    if entry.game.is_over() {
+        return Response::error(409, "that game is over");
+    }
+
+    let line = match request.guess {
+        serde_json::Value::String(line) => line,
+        value => value.to_string(),
+    };
+    let (outcome, rejected) = match entry.game.submit(&line) {
+        Ok(outcome) => (Some(outcome), None),
+        Err(rejection) => (None, Some(rejection.to_string())),
+    };
+
+    Response::json(
+        200,
+        &GuessReply {
+            outcome,
+            rejected,
+            game: state(id, entry),
+        },
+    )
+}
+
+fn find<'a>(games: &'a Games, id: &str) -> Option<(u64, &'a Running)> {
+    let id = id.parse().ok()?;
+    games.games.get(&id).map(|entry| (id, entry))
+}
+
+fn not_found(id: &str) -> Response {
+    Response::error(404, format!("no game with id '{id}'"))
+}
+
+fn state(id: u64, entry: &Running) -> GameState {
+    let game = &entry.game;
+
+    GameState {
+        id,
+        low: game.range().low(),
+        high: game.range().high(),
+        seed: game.is_over().then_some(entry.seed),
+        attempts: game.attempts(),
+        attempts_left: game.attempts_left(),
+        won: game.is_won(),
+        over: game.is_over(),
+        secret: game.is_over().then(|| game.secret()),
+        history: game
+            .history()
+            .iter()
+            .map(|turn| match turn {
+                Turn::Guess(guess, outcome) => Entry::Guess {
+                    guess: *guess,
+                    outcome: *outcome,
+                },
+                Turn::Rejected(input, rejection) => Entry::Rejected {
+                    input: input.clone(),
+                    rejected: rejection.to_string(),
+                },
+            })
+            .collect(),
+    }
+}
+
+/// Talks to a [`serve`]r, one connection per request like the server expects.
+#[derive(Debug, Clone)]
+pub struct Client {
+    addr: String,
+}
+
+impl Client {
+    /// Doesn't connect yet, every request makes its own connection.
+    pub fn new(addr: impl Into<String>) -> Client {
+        Client { addr: addr.into() }
+    }
+
+    pub fn create(&self, new: &NewGame) -> io::Result<GameState> {
+        self.request("POST", "/games", Some(new))
+    }
+
+    pub fn state(&self, id: u64) -> io::Result<GameState> {
+        self.request::<(), _>("GET", &format!("/games/{id}"), None)
+    }
+
+    pub fn guess(&self, id: u64, guess: &str) -> io::Result<GuessReply> {
+        let body = GuessRequest {
+            guess: guess.trim().into(),
+        };
+        self.request("POST", &format!("/games/{id}/guesses"), Some(&body))
+    }
+
+    pub fn delete(&self, id: u64) -> io::Result<()> {
+        self.request::<(), serde_json::Value>("DELETE", &format!("/games/{id}"), None)
+            .map(|_| ())
+    }
+
+    fn request<B, T>(&self, method: &str, path: &str, body: Option<&B>) -> io::Result<T>
+    where
+        B: Serialize,
+        T: for<'de> Deserialize<'de>,
+    {
+        let body = match body {
+            Some(body) => serde_json::to_string(body)?,
+            None => String::new(),
+        };
+
+        let addr =
+            self.addr.to_socket_addrs()?.next().ok_or_else(|| {
+                io::Error::new(ErrorKind::InvalidInput, "no address to connect to")
+            })?;
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            self.addr,
+            body.len()
+        )?;
+        stream.flush()?;
+
+        // the server always closes the connection, so the body is whatever's left
+        let mut response = String::new();
+        BufReader::new(stream).read_to_string(&mut response)?;
+
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "malformed response"))?;
+        let status: u16 = head
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "malformed status line"))?;
+
+        if !(200..300).contains(&status) {
+            let error = serde_json::from_str::<ErrorBody>(body)
+                .map(|e| e.error)
+                .unwrap_or_else(|_| body.to_string());
+            return Err(io::Error::other(format!("{status}: {error}")));
+        }
+
+        serde_json::from_str(body).map_err(io::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a server on a free port, left running for the rest of the tests
+    fn serving(config: Config) -> Client {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, config));
+
+        Client::new(addr.to_string())
+    }
+
+    #[test]
+    fn a_game_from_start_to_finish() {
+        let client = serving(Config::default());
+        let new = NewGame {
+            seed: Some(7),
+            ..NewGame::default()
+        };
+
+        let state = client.create(&new).unwrap();
+        assert_eq!((state.low, state.high), (1, 100));
+        assert_eq!((state.seed, state.secret), (None, None));
+
+        let reply = client.guess(state.id, "abc").unwrap();
+        assert_eq!(reply.outcome, None);
+        assert!(reply.rejected.is_some());
+
+        let secret = Game::seeded(&Config::default(), 7).secret();
+        let reply = client.guess(state.id, &secret.to_string()).unwrap();
+        assert_eq!(reply.outcome, Some(Outcome::Win));
+
+        let state = client.state(state.id).unwrap();
+        assert!(state.won && state.over);
+        assert_eq!((state.seed, state.secret), (Some(7), Some(secret)));
+        assert_eq!(state.history.len(), 2);
+
+        client.delete(state.id).unwrap();
+        assert!(client.state(state.id).is_err());
+    }
+
+    #[test]
+    fn each_game_gets_its_own_seed() {
+        let client = serving(Config {
+            seed: Some(100),
+            ..Config::default()
+        });
+        let new = NewGame {
+            max_attempts: Some(1),
+            ..NewGame::default()
+        };
+
+        for n in 1..=2 {
+            let id = client.create(&new).unwrap().id;
+            let state = client.guess(id, "50").unwrap().game;
+
+            assert!(state.over);
+            assert_eq!(state.seed, Some(100 + n));
+        }
+    }
+
+    #[test]
+    fn finished_games_make_room_and_live_ones_dont() {
+        let mut games = Games::default();
+        let config = Config {
+            max_attempts: Some(1),
+            ..Config::default()
+        };
+
+        for _ in 0..MAX_GAMES {
+            assert_eq!(create(b"", &mut games, &config).status, 201);
+        }
+        assert_eq!(create(b"", &mut games, &config).status, 429);
+        assert_eq!(games.games.len(), MAX_GAMES);
+
+        // one guess each is all they get, so these two are over whatever the guess was
+        for id in [5, 3] {
+            guess(br#"{"guess": 50}"#, &mut games, &id.to_string());
+        }
+        assert_eq!(create(b"", &mut games, &config).status, 201);
+        assert!(!games.games.contains_key(&3));
+        assert!(games.games.contains_key(&5));
+
+        assert_eq!(create(b"", &mut games, &config).status, 201);
+        assert!(!games.games.contains_key(&5));
+        assert_eq!(create(b"", &mut games, &config).status, 429);
+    }
+}
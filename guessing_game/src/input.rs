@@ -0,0 +1,97 @@
+use crate::clock::Timeout;
+use crate::config::Range;
+use crate::messages::Messages;
+use crate::number::Number;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Reads one line from `reader`, or `None` once the input has run out.
+///
+/// `read_line` signals end of input (a closed pipe, Ctrl-D) by reading nothing at all, which the
+/// old loop treated as a bad guess and asked again, forever.
+pub fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+
+    match reader.read_line(&mut line)? {
+        0 => Ok(None),
+        _ => Ok(Some(line)),
+    }
+}
+
+/// Reads lines from `reader` on a thread of their own, so that waiting for the next one can time
+/// out. The channel closes at end of input, or after passing on a read error.
+pub fn spawn_lines<R>(mut reader: R) -> Receiver<io::Result<String>>
+where
+    R: BufRead + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || loop {
+        match read_line(&mut reader) {
+            Ok(Some(line)) => {
+                if sender.send(Ok(line)).is_err() {
+                    break;
+                }
+            }
+            Ok(None) => break,
+            Err(err) => {
+                let _ = sender.send(Err(err));
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Why a line of input wasn't accepted as a guess.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rejection {
+    Empty,
+    NotANumber(String),
+    Negative(i64, Range),
+    OutOfRange(i64, Range),
+    /// Out of range in a game over some other kind of number, see [`crate::number`].
+    Outside {
+        guess: String,
+        low: String,
+        high: String,
+    },
+    Overflow(String),
+    /// A bulls and cows code with the wrong number of digits.
+    WrongLength {
+        got: usize,
+        expected: usize,
+    },
+    RepeatedDigit(char),
+    /// Arrived after the time limit, which loses the game.
+    TimedOut(Timeout),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().rejection(self))
+    }
+}
+
+/// Turns a line of input into a guess, or says why it can't be one.
+pub fn parse_guess(line: &str, range: Range) -> Result<i64, Rejection> {
+    let text = line.trim();
+
+    let guess = i64::parse(text)?;
+
+    // a negative guess only gets its own message when the range can't hold negatives at all
+    if guess < 0 && range.low() >= 0 {
+        return Err(Rejection::Negative(guess, range));
+    }
+
+    if !range.contains(guess) {
+        return Err(Rejection::OutOfRange(guess, range));
+    }
+
+    Ok(guess)
+}
@@ -0,0 +1,34 @@
+//! The guessing game from chapter 2, pulled out of `main` so it can be driven by something other
+//! than a terminal.
+pub mod batch;
+pub mod bulls;
+pub mod clock;
+pub mod coach;
+pub mod config;
+pub mod find_all;
+pub mod game;
+pub mod hints;
+pub mod http;
+pub mod input;
+pub mod messages;
+pub mod net;
+pub mod number;
+pub mod round;
+pub mod score;
+pub mod solver;
+pub mod stats;
+pub mod transcript;
+pub mod tui;
+
+pub use bulls::BullsAndCows;
+pub use clock::{Clock, ManualClock, SystemClock, Timeout, Timer};
+pub use config::{Config, ConfigError, Difficulty, Range};
+pub use find_all::FindAll;
+pub use game::{Game, Outcome, Turn};
+pub use hints::{Band, Hint, HintPolicy, Trend};
+pub use input::Rejection;
+pub use messages::{Locale, Messages};
+pub use number::{Number, NumericGame};
+pub use round::{Answer, GameKind, Round, Secret};
+pub use score::{HighScore, HighScores};
+pub use transcript::Transcript;
@@ -1,35 +1,1085 @@
-use rand::Rng;
-use std::cmp::Ordering;
-use std::io;
+use guessing_game::config::{self, ConfigError};
+use guessing_game::net::{self, Client, Reply};
+use guessing_game::stats::{self, GameRecord, History};
+use guessing_game::{batch, http};
+use guessing_game::{
+    clock, find_all, input, messages, number, solver, transcript, tui, BullsAndCows, Config,
+    FindAll, Game, GameKind, HighScore, HighScores, Locale, Messages, Outcome, Rejection, Round,
+    SystemClock, Transcript,
+};
+use messages::Key;
+use std::fs::File;
+use std::io::{BufReader, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+use std::{env, io, process};
+
+// 0 is a finished game, 2 is a bad command line
+const EXIT_USAGE: i32 = 2;
+const EXIT_END_OF_INPUT: i32 = 3;
+const EXIT_READ_ERROR: i32 = 4;
+const EXIT_DIVERGED: i32 = 5;
+const EXIT_NETWORK: i32 = 6;
+const EXIT_BAD_CATALOG: i32 = 7;
 
 fn main() {
-    println!("Guess the number!");
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let messages = messages_from(&args);
+
+    let result = match args.first().map(String::as_str) {
+        Some("bot") => bot(args.split_off(1)),
+        Some("batch") => batch(args.split_off(1)),
+        Some("replay") => replay(args.split_off(1), &messages),
+        Some("host") => host(args.split_off(1)),
+        Some("join") => join(args.split_off(1)),
+        Some("serve") => serve(args.split_off(1)),
+        Some("stats") => stats(args.split_off(1)),
+        Some("bulls") => bulls(args.split_off(1)),
+        Some("find-all") => find_all(args.split_off(1)),
+        Some("numbers") => numbers(args.split_off(1)),
+        Some("check-messages") => check_messages(&messages),
+        _ => Config::from_args(args).map(play),
+    };
+
+    if let Err(err) = result {
+        error(&messages, messages.config_error(&err));
+        process::exit(EXIT_USAGE);
+    }
+}
+
+/// `guessing_game bot [--strategy NAME|all] [--games N]`: lets the built-in players loose on the
+/// engine and reports how many guesses each needed.
+fn bot(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let strategy = config::take_option(&mut args, "--strategy")?.unwrap_or_else(|| "all".into());
+    let games = config::take_number(&mut args, "--games")?.unwrap_or(1000);
+    let config = Config::from_args(args)?;
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let messages = messages_for(&config);
+
+    let names: Vec<&str> = match strategy.as_str() {
+        "all" => solver::STRATEGIES.to_vec(),
+        name => vec![name],
+    };
+    let strategies = names
+        .into_iter()
+        .map(|name| {
+            solver::strategy(name, seed, config.lies)
+                .ok_or(ConfigError::UnknownStrategy(name.into()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    println!("{}", messages.format(Key::Seed, &[("seed", &seed)]));
+    println!(
+        "{}",
+        messages.format(
+            Key::BotGames,
+            &[
+                ("games", &games),
+                ("range", &messages.range(config.range)),
+                ("bound", &solver::binary_search_bound(config.range)),
+            ]
+        )
+    );
+    println!();
+
+    // each column at least as wide as its heading, whatever the language
+    let headings = [
+        Key::StrategyColumn,
+        Key::GamesColumn,
+        Key::WinsColumn,
+        Key::MeanColumn,
+        Key::MaxColumn,
+    ]
+    .map(|key| messages.get(key));
+    let width = |i: usize, least: usize| least.max(headings[i].chars().count());
+    let (strategy_width, games_width, wins_width, mean_width, max_width) = (
+        width(0, 8),
+        width(1, 6),
+        width(2, 6),
+        width(3, 8),
+        width(4, 5),
+    );
+    println!(
+        "{:<strategy_width$} {:>games_width$} {:>wins_width$} {:>mean_width$} {:>max_width$}",
+        headings[0], headings[1], headings[2], headings[3], headings[4]
+    );
+
+    let mut reports = Vec::new();
+    for mut strategy in strategies {
+        let report = solver::run(&config, strategy.as_mut(), games, seed);
+
+        println!(
+            "{:<strategy_width$} {:>games_width$} {:>wins_width$} {:>mean_width$.2} {:>max_width$}",
+            report.strategy,
+            report.games,
+            report.wins,
+            report.mean_attempts(),
+            report.max_attempts
+        );
+        reports.push(report);
+    }
+
+    for report in &reports {
+        println!();
+        println!(
+            "{}",
+            messages.format(Key::AttemptsToWin, &[("strategy", &report.strategy)])
+        );
+
+        for line in stats::histogram(&report.histogram) {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// `guessing_game batch [--script FILE]...`: plays scripted games, see [`batch`], and prints how
+/// each one went as a line of JSON. Without a `--script`, or with `-`, the script comes from
+/// stdin.
+///
+/// Game `n` is seeded with `--seed` plus `n - 1`, so a fixed seed gives the same results every
+/// time.
+fn batch(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let mut paths = Vec::new();
+    while let Some(path) = config::take_option(&mut args, "--script")? {
+        paths.push(path);
+    }
+    let config = Config::from_args(args)?;
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let messages = messages_for(&config);
+
+    if paths.is_empty() {
+        paths.push(String::from("-"));
+    }
+
+    let mut games = Vec::new();
+    for path in &paths {
+        let script = match path.as_str() {
+            "-" => batch::games(io::stdin().lock()),
+            path => File::open(path).and_then(|file| batch::games(BufReader::new(file))),
+        };
+        match script {
+            Ok(script) => games.extend(script),
+            Err(err) => {
+                error(
+                    &messages,
+                    messages.format(Key::CouldntRead, &[("path", path), ("err", &err)]),
+                );
+                process::exit(EXIT_READ_ERROR);
+            }
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    for (i, lines) in games.iter().enumerate() {
+        let summary = batch::play(&config, i + 1, seed.wrapping_add(i as u64), lines);
+        let written = serde_json::to_writer(&mut stdout, &summary)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(stdout));
+        // the harness has stopped listening, no point carrying on
+        if written.is_err() {
+            process::exit(EXIT_READ_ERROR);
+        }
+    }
+
+    Ok(())
+}
+
+/// `guessing_game replay FILE`: plays a `--transcript` back against the engine.
+fn replay(args: Vec<String>, messages: &Messages) -> Result<(), ConfigError> {
+    let [path] = args.as_slice() else {
+        return Err(ConfigError::MissingValue(String::from("replay")));
+    };
+
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => {
+            error(
+                messages,
+                messages.format(Key::CouldntOpen, &[("path", path), ("err", &err)]),
+            );
+            process::exit(EXIT_READ_ERROR);
+        }
+    };
+
+    match transcript::replay(BufReader::new(file)) {
+        Ok(report) if report.divergences.is_empty() => {
+            println!(
+                "{}",
+                messages.format(Key::Replayed, &[("path", path), ("turns", &report.turns)])
+            );
+            Ok(())
+        }
+        Ok(report) => {
+            for divergence in &report.divergences {
+                println!("{path}: {}", messages.divergence(divergence));
+            }
+            println!(
+                "{}",
+                messages.format(
+                    Key::ReplayedDiverged,
+                    &[
+                        ("path", path),
+                        ("turns", &report.turns),
+                        ("diverged", &report.divergences.len()),
+                    ]
+                )
+            );
+            process::exit(EXIT_DIVERGED);
+        }
+        Err(err) => {
+            error(messages, format!("{path}: {}", messages.replay_error(&err)));
+            process::exit(EXIT_READ_ERROR);
+        }
+    }
+}
+
+/// `guessing_game host [--addr ADDR] [--secret N] [--timeout SECS]`: picks a secret and waits for
+/// someone to `join` and guess it.
+fn host(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let addr = config::take_option(&mut args, "--addr")?.unwrap_or_else(|| "127.0.0.1:7878".into());
+    let secret = config::take_number(&mut args, "--secret")?;
+    let timeout = Duration::from_secs(config::take_number(&mut args, "--timeout")?.unwrap_or(60));
+    let config = Config::from_args(args)?;
+    let messages = messages_for(&config);
+
+    let mut game = match secret {
+        Some(secret) if !config.range.contains(secret) => {
+            return Err(ConfigError::SecretOutOfRange {
+                secret,
+                range: config.range,
+            })
+        }
+        Some(secret) => Game::new(&config, secret),
+        None => Game::seeded(&config, config.seed.unwrap_or_else(rand::random)),
+    };
+
+    let listener =
+        TcpListener::bind(&addr).unwrap_or_else(|err| network_error(&addr, err, &messages));
+    let local = listener
+        .local_addr()
+        .map_or(addr.clone(), |a| a.to_string());
+    println!(
+        "{}",
+        messages.format(
+            Key::Hosting,
+            &[("addr", &local), ("secret", &game.secret())]
+        )
+    );
+
+    if let Err(err) = net::host(&listener, &mut game, timeout) {
+        network_error(&addr, err, &messages);
+    }
+
+    for turn in game.history() {
+        println!("{}", messages.turn(turn));
+    }
+
+    if game.is_won() {
+        println!(
+            "{}",
+            messages.format(Key::TheyWon, &[("attempts", &game.attempts())])
+        );
+    } else if game.is_lost() {
+        println!("{}", messages.get(Key::TheyLost));
+    } else {
+        println!("{}", messages.get(Key::TheyLeft));
+    }
+
+    Ok(())
+}
+
+/// `guessing_game join [ADDR] [--timeout SECS] [--lang LANG]`: guesses at someone else's secret.
+fn join(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let timeout = Duration::from_secs(config::take_number(&mut args, "--timeout")?.unwrap_or(60));
+    let messages = match config::take_option(&mut args, "--lang")? {
+        Some(tag) => Messages::new(Locale::parse(&tag)?),
+        None => Messages::new(Locale::from_env()),
+    };
+    let addr = match args.as_slice() {
+        [] => String::from("127.0.0.1:7878"),
+        [addr] => addr.clone(),
+        [_, extra, ..] => return Err(ConfigError::UnknownOption(extra.clone())),
+    };
+
+    let mut client =
+        Client::connect(&addr, timeout).unwrap_or_else(|err| network_error(&addr, err, &messages));
+    let range = client.range();
+    let mut stdin = io::stdin().lock();
+
+    let range = messages.range(range);
+
+    println!("{}", messages.get(Key::GuessTheNumber));
+
+    loop {
+        println!("{}", messages.format(Key::PleaseInput, &[("what", &range)]));
+
+        let guess = match input::read_line(&mut stdin) {
+            Ok(Some(line)) => line,
+            Ok(None) => {
+                let _ = client.quit();
+                process::exit(EXIT_END_OF_INPUT);
+            }
+            Err(err) => {
+                error(
+                    &messages,
+                    messages.format(Key::ReadFailed, &[("err", &err)]),
+                );
+                let _ = client.quit();
+                process::exit(EXIT_READ_ERROR);
+            }
+        };
+
+        match client.guess(&guess) {
+            Ok(Reply::Outcome(Outcome::Win)) => {
+                println!("{}", you_guessed(&messages, &guess));
+                println!("{}", messages.outcome(Outcome::Win));
+                return Ok(());
+            }
+            Ok(Reply::Outcome(outcome)) => {
+                println!("{}", you_guessed(&messages, &guess));
+                println!(
+                    "{}",
+                    messages.format(
+                        Key::SecretIs,
+                        &[("outcome", &messages.outcome(outcome)), ("range", &range)]
+                    )
+                );
+            }
+            Ok(Reply::Rejected(rejection)) => println!("{}", messages.rejection(&rejection)),
+            Ok(Reply::Lost { secret, last }) => {
+                let key = match last {
+                    Ok(outcome) => {
+                        println!("{}", you_guessed(&messages, &guess));
+                        println!("{}", messages.outcome(outcome));
+                        Key::OutOfGuesses
+                    }
+                    Err(Rejection::TimedOut(timeout)) => {
+                        println!("{}", messages.timeout(timeout));
+                        Key::OutOfTime
+                    }
+                    Err(rejection) => {
+                        println!("{}", messages.rejection(&rejection));
+                        Key::OutOfGuesses
+                    }
+                };
+                println!("{}", messages.format(key, &[("secret", &secret)]));
+                return Ok(());
+            }
+            Err(err) => network_error(&addr, err, &messages),
+        }
+    }
+}
+
+/// `guessing_game serve [--addr ADDR]`: runs games for anyone who asks over HTTP, see
+/// [`http`]. The usual options set the defaults for new games.
+fn serve(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let addr = config::take_option(&mut args, "--addr")?.unwrap_or_else(|| "127.0.0.1:8080".into());
+    let config = Config::from_args(args)?;
+    let messages = messages_for(&config);
+
+    let listener =
+        TcpListener::bind(&addr).unwrap_or_else(|err| network_error(&addr, err, &messages));
+    let url = format!(
+        "http://{}",
+        listener
+            .local_addr()
+            .map_or(addr.clone(), |a| a.to_string())
+    );
+    println!("{}", messages.format(Key::ServingOn, &[("url", &url)]));
+
+    if let Err(err) = http::serve(listener, config) {
+        network_error(&addr, err, &messages);
+    }
+
+    Ok(())
+}
+
+fn you_guessed(messages: &Messages, guess: &str) -> String {
+    messages.format(Key::YouGuessed, &[("guess", &guess.trim())])
+}
+
+fn network_error(addr: &str, err: io::Error, messages: &Messages) -> ! {
+    error(messages, format!("{addr}: {err}"));
+    process::exit(EXIT_NETWORK);
+}
+
+/// `guessing_game bulls [--digits N]`: bulls and cows, guessing a code of distinct digits.
+fn bulls(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let digits = config::take_number(&mut args, "--digits")?.unwrap_or(4);
+    if !(1..=10).contains(&digits) {
+        return Err(ConfigError::InvalidNumber {
+            option: String::from("--digits"),
+            value: digits.to_string(),
+        });
+    }
+    let config = Config::from_args(args)?;
+    config.check_plain("bulls")?;
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let messages = messages_for(&config);
+    let label = config.filed_under(format!("bulls {digits}"));
+    let (scores_path, mut high_scores) = load_scores(&config, &messages);
+
+    println!(
+        "{}",
+        messages.format(Key::GuessTheCode, &[("digits", &digits)])
+    );
+    println!("{}", messages.format(Key::Seed, &[("seed", &seed)]));
+    show_best(&config, &high_scores, &label, &messages);
+
+    let mut game = BullsAndCows::seeded(&config, digits, seed);
+    let mut transcript = open_transcript(&config, &messages);
+    log(&mut transcript, &messages, |t| {
+        t.start(&config, seed, GameKind::Bulls, Some(digits))
+    });
+
+    run_round(&mut game, &mut transcript, &messages);
+
+    if game.is_won() {
+        println!(
+            "{}",
+            messages.format(Key::Cracked, &[("attempts", &game.attempts())])
+        );
+    }
+    finish(
+        &config,
+        &game,
+        &label,
+        &messages,
+        &scores_path,
+        &mut high_scores,
+    );
+
+    Ok(())
+}
+
+/// `guessing_game find-all [--secrets N] [--each]`: several secrets, and the round goes on until
+/// they've all been found. Answers are for the nearest secret still hidden, or with `--each` for
+/// every secret.
+fn find_all(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let count = config::take_number(&mut args, "--secrets")?.unwrap_or(3);
+    let each = match args.iter().position(|arg| arg == "--each") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+    if !(1..=find_all::MAX_SECRETS).contains(&count) {
+        return Err(ConfigError::NotBetween {
+            option: "--secrets",
+            low: 1,
+            high: find_all::MAX_SECRETS,
+        });
+    }
+    let config = Config::from_args(args)?;
+    config.check_plain("find-all")?;
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let messages = messages_for(&config);
+
+    let mut game = FindAll::seeded(&config, count, each, seed);
+    let kind = game.kind();
+    let count = game.secrets().len();
+    let name = if each { "find-each" } else { "find-all" };
+    let label = config.filed_under(format!("{name} {count} {}", config.label()));
+    let (scores_path, mut high_scores) = load_scores(&config, &messages);
+
+    println!("{}", messages.format(Key::FindAll, &[("count", &count)]));
+    println!("{}", messages.format(Key::Seed, &[("seed", &seed)]));
+    show_best(&config, &high_scores, &label, &messages);
+
+    let mut transcript = open_transcript(&config, &messages);
+    log(&mut transcript, &messages, |t| {
+        t.start(&config, seed, kind, Some(count))
+    });
+
+    run_round(&mut game, &mut transcript, &messages);
+
+    if game.is_won() {
+        println!(
+            "{}",
+            messages.format(Key::FoundAll, &[("attempts", &game.attempts())])
+        );
+    }
+    finish(
+        &config,
+        &game,
+        &label,
+        &messages,
+        &scores_path,
+        &mut high_scores,
+    );
+
+    Ok(())
+}
+
+/// `guessing_game numbers [--type i64|u64|i128|decimal] [--min N] [--max N] [--tolerance N]`:
+/// the number game over ranges an `i64` can't hold, or decimals, where a guess within the
+/// tolerance of the secret wins.
+fn numbers(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let kind = config::take_option(&mut args, "--type")?.unwrap_or_else(|| "decimal".into());
+    // taken before `Config` sees them, it would insist on `i64`s
+    let min = config::take_option(&mut args, "--min")?;
+    let max = config::take_option(&mut args, "--max")?;
+    let tolerance = config::take_option(&mut args, "--tolerance")?;
+    let config = Config::from_args(args)?;
+    config.check_plain("numbers")?;
+    let setup = number::Setup::new(&kind, min, max, tolerance)?;
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut game = setup.game(&config, seed, Box::new(SystemClock::new()))?;
+    let messages = messages_for(&config);
+    let label = config.filed_under(setup.label());
+    let (scores_path, mut high_scores) = load_scores(&config, &messages);
+
+    println!("{}", messages.get(Key::GuessTheNumber));
+    println!("{}", messages.format(Key::Seed, &[("seed", &seed)]));
+    show_best(&config, &high_scores, &label, &messages);
+
+    let mut transcript = open_transcript(&config, &messages);
+    log(&mut transcript, &messages, |t| {
+        t.start_numbers(&config, seed, &setup)
+    });
+
+    run_round(game.as_mut(), &mut transcript, &messages);
+    finish(
+        &config,
+        game.as_ref(),
+        &label,
+        &messages,
+        &scores_path,
+        &mut high_scores,
+    );
+
+    Ok(())
+}
+
+/// `guessing_game check-messages`: makes sure every translation has the placeholders the English
+/// one does. Missing messages don't get this far, they don't compile.
+fn check_messages(messages: &Messages) -> Result<(), ConfigError> {
+    let problems = messages::check();
+    for problem in &problems {
+        println!("{}", messages.problem(problem));
+    }
+
+    if !problems.is_empty() {
+        process::exit(EXIT_BAD_CATALOG);
+    }
+
+    println!(
+        "{}",
+        messages.format(
+            Key::CatalogOk,
+            &[("keys", &Key::ALL.len()), ("languages", &Locale::ALL.len())]
+        )
+    );
+    Ok(())
+}
+
+/// `guessing_game stats [--player NAME] [--format text|csv|json]`: everyone's lifetime numbers,
+/// or just one player's.
+fn stats(mut args: Vec<String>) -> Result<(), ConfigError> {
+    let format = match config::take_option(&mut args, "--format")? {
+        Some(name) => stats::Format::parse(&name).ok_or(ConfigError::UnknownFormat(name))?,
+        None => stats::Format::Text,
+    };
+    let config = Config::from_args(args)?;
+    let messages = messages_for(&config);
+    let path = config
+        .stats_path
+        .clone()
+        .unwrap_or_else(History::default_path);
+
+    let history = match History::load(&path) {
+        Ok(history) => history,
+        Err(err) => {
+            error(
+                &messages,
+                messages.format(
+                    Key::CouldntRead,
+                    &[("path", &path.display()), ("err", &err)],
+                ),
+            );
+            process::exit(EXIT_READ_ERROR);
+        }
+    };
+    skipped(&messages, history.skipped, &path);
+
+    let players = match &config.player {
+        Some(player) => vec![player.as_str()],
+        None => history.players(),
+    };
+    let stats: Vec<_> = players.iter().map(|p| history.stats(p)).collect();
+
+    if stats.is_empty() && format == stats::Format::Text {
+        println!("{}", messages.get(Key::NoGamesYet));
+    }
+    print!("{}", stats::export(&stats, format, &messages));
+
+    Ok(())
+}
+
+fn record_game(config: &Config, game: GameRecord, messages: &Messages) {
+    let path = config
+        .stats_path
+        .clone()
+        .unwrap_or_else(History::default_path);
+    if let Err(err) = History::append(&path, &game) {
+        warn(
+            messages,
+            messages.format(Key::CouldntSaveStats, &[("err", &err)]),
+        );
+    }
+}
+
+fn player(config: &Config) -> String {
+    config
+        .player
+        .clone()
+        .or_else(|| env::var("USER").ok())
+        .unwrap_or_else(|| String::from("player"))
+}
+
+fn messages_for(config: &Config) -> Messages {
+    Messages::new(config.lang.unwrap_or_else(Locale::from_env))
+}
+
+// for when the command line might not parse: a usable --lang is honoured, anything else falls
+// back to the environment
+fn messages_from(args: &[String]) -> Messages {
+    let lang = args
+        .iter()
+        .position(|arg| arg == "--lang")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|tag| Locale::parse(tag).ok());
+    Messages::new(lang.unwrap_or_else(Locale::from_env))
+}
+
+fn error(messages: &Messages, error: impl std::fmt::Display) {
+    eprintln!("{}", messages.format(Key::Error, &[("error", &error)]));
+}
+
+fn warn(messages: &Messages, warning: impl std::fmt::Display) {
+    eprintln!(
+        "{}",
+        messages.format(Key::Warning, &[("warning", &warning)])
+    );
+}
+
+fn skipped(messages: &Messages, count: usize, path: &Path) {
+    if count > 0 {
+        warn(
+            messages,
+            messages.format(
+                Key::SkippedLines,
+                &[("count", &count), ("path", &path.display())],
+            ),
+        );
+    }
+}
 
-    let secret_number = rand::thread_rng().gen_range(1..=100);
+/// Reads guesses from stdin until the round is over, whichever game it is. Running out of input
+/// ends the program.
+fn run_round(
+    round: &mut dyn Round,
+    transcript: &mut Option<Transcript<File>>,
+    messages: &Messages,
+) {
+    let lines = input::spawn_lines(BufReader::new(io::stdin()));
 
     loop {
-        println!("Please input your guess.");
+        let mut limits = Vec::new();
+        if let Some(left) = round.attempts_left() {
+            limits.push(messages.format(Key::AttemptsLeft, &[("left", &left)]));
+        }
+        if let Some(time) = round.time_left() {
+            limits.push(messages.format(Key::TimeToGo, &[("time", &clock::clock(time))]));
+        }
+        let what = round.prompt(messages);
+        let prompt = match limits.is_empty() {
+            true => messages.format(Key::PleaseInput, &[("what", &what)]),
+            false => messages.format(
+                Key::PleaseInputLimits,
+                &[("what", &what), ("limits", &limits.join(", "))],
+            ),
+        };
+        println!("{prompt}");
 
-        let mut guess = String::new();
+        let guess = match next_line(&lines, round.time_left()) {
+            Line::Text(line) => line,
+            Line::TimedOut => {
+                if let Some(timeout) = round.check_time() {
+                    println!("{}", messages.timeout(timeout));
+                }
+                if round.is_over() {
+                    break;
+                }
+                continue;
+            }
+            Line::End => {
+                println!(
+                    "{}",
+                    messages.format(Key::NoMoreInput, &[("secret", &round.secret())])
+                );
+                log(transcript, messages, |t| t.end(round));
+                process::exit(EXIT_END_OF_INPUT);
+            }
+            Line::Error(err) => {
+                error(messages, messages.format(Key::ReadFailed, &[("err", &err)]));
+                log(transcript, messages, |t| t.end(round));
+                process::exit(EXIT_READ_ERROR);
+            }
+        };
 
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
+        let result = round.answer(&guess);
+        log(transcript, messages, |t| t.turn(&guess, &result));
+
+        match result {
+            Ok(answer) => {
+                println!("{}", you_guessed(messages, &guess));
+                println!("{}", round.feedback(&answer, messages));
+            }
+            Err(rejection) => println!("{}", messages.rejection(&rejection)),
+        }
 
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
+        if round.is_over() {
+            break;
+        }
+    }
+
+    log(transcript, messages, |t| t.end(round));
+
+    if round.is_lost() {
+        let key = match round.check_time() {
+            Some(_) => Key::OutOfTime,
+            None => Key::OutOfGuesses,
         };
+        println!("{}", messages.format(key, &[("secret", &round.secret())]));
+    }
+}
+
+enum Line {
+    Text(String),
+    TimedOut,
+    End,
+    Error(io::Error),
+}
+
+/// Waits for the next line of input, but no longer than `limit`.
+fn next_line(lines: &Receiver<io::Result<String>>, limit: Option<Duration>) -> Line {
+    let line = match limit {
+        Some(limit) => match lines.recv_timeout(limit) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => return Line::TimedOut,
+            Err(RecvTimeoutError::Disconnected) => return Line::End,
+        },
+        None => match lines.recv() {
+            Ok(line) => line,
+            Err(_) => return Line::End,
+        },
+    };
+
+    match line {
+        Ok(line) => Line::Text(line),
+        Err(err) => Line::Error(err),
+    }
+}
+
+/// [`run_round`], but redrawing the whole screen after every guess.
+fn run_tui(game: &mut Game, transcript: &mut Option<Transcript<File>>, messages: &Messages) {
+    let lines = input::spawn_lines(BufReader::new(io::stdin()));
+    let mut stdout = io::stdout();
+    let mut message = messages.format(Key::PleaseInput, &[("what", &game.prompt(messages))]);
 
-        println!("You guessed: {}", guess);
+    print!("{}", tui::ENTER);
 
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("You Win!");
-                break;
+    let finished = loop {
+        print!("{}{}", tui::CLEAR, tui::render(game, &message, messages));
+        print!(" {}", messages.get(Key::GuessField));
+        let _ = stdout.flush();
+
+        // wake up once a second to keep the clock on screen ticking
+        let wait = game.time_left().map_or(Duration::from_secs(1), |left| {
+            left.min(Duration::from_secs(1))
+        });
+
+        let guess = match next_line(&lines, Some(wait)) {
+            Line::Text(line) => line,
+            Line::TimedOut => match game.check_time() {
+                Some(timeout) => {
+                    message = messages.timeout(timeout);
+                    break true;
+                }
+                None => continue,
+            },
+            Line::End => break false,
+            Line::Error(err) => {
+                message = messages.format(
+                    Key::Error,
+                    &[("error", &messages.format(Key::ReadFailed, &[("err", &err)]))],
+                );
+                break false;
             }
+        };
+
+        let result = game.answer(&guess);
+        log(transcript, messages, |t| t.turn(&guess, &result));
+
+        message = match result {
+            Ok(answer) => game.feedback(&answer, messages),
+            Err(rejection) => messages.rejection(&rejection),
+        };
+
+        if game.is_over() {
+            break true;
+        }
+    };
+
+    print!("{}", tui::LEAVE);
+    log(transcript, messages, |t| t.end(game));
+
+    // leave the final state behind on the normal screen
+    print!("{}", tui::render(game, &message, messages));
+    let secret = game.secret();
+    if !finished {
+        println!(
+            "{}",
+            messages.format(Key::NoMoreInput, &[("secret", &secret)])
+        );
+        process::exit(EXIT_END_OF_INPUT);
+    }
+    if game.timed_out().is_some() {
+        println!(
+            "{}",
+            messages.format(Key::OutOfTime, &[("secret", &secret)])
+        );
+    } else if game.is_lost() {
+        println!(
+            "{}",
+            messages.format(Key::OutOfGuesses, &[("secret", &secret)])
+        );
+    }
+}
+
+fn open_transcript(config: &Config, messages: &Messages) -> Option<Transcript<File>> {
+    config.transcript_path.as_ref().and_then(|path| {
+        File::create(path)
+            .map(Transcript::new)
+            .map_err(|err| {
+                warn(
+                    messages,
+                    messages.format(
+                        Key::CouldntCreate,
+                        &[("path", &path.display()), ("err", &err)],
+                    ),
+                )
+            })
+            .ok()
+    })
+}
+
+/// The game as the book has it: guess on stdin until you get it.
+fn play(config: Config) {
+    let label = config.label();
+    let messages = messages_for(&config);
+    let (scores_path, mut high_scores) = load_scores(&config, &messages);
+
+    // always seeded, so a player can hand us the seed from a bug report and we get the same game
+    let seed = config.seed.unwrap_or_else(rand::random);
+
+    println!("{}", messages.get(Key::GuessTheNumber));
+    println!("{}", messages.format(Key::Seed, &[("seed", &seed)]));
+    if config.lies > 0 {
+        println!(
+            "{}",
+            messages.format(Key::MayLie, &[("lies", &config.lies)])
+        );
+    }
+    show_best(&config, &high_scores, &label, &messages);
+
+    let mut game = Game::seeded(&config, seed);
+    let mut transcript = open_transcript(&config, &messages);
+    log(&mut transcript, &messages, |t| {
+        t.start(&config, seed, GameKind::Number, None)
+    });
+
+    // escape codes in a pipe are just noise, so the full-screen view needs a real terminal
+    if config.tui && io::stdout().is_terminal() {
+        run_tui(&mut game, &mut transcript, &messages);
+    } else {
+        if config.tui {
+            warn(&messages, messages.get(Key::TuiNeedsTerminal));
+        }
+        run_round(&mut game, &mut transcript, &messages);
+    }
+
+    if game.max_lies() > 0 {
+        let lies: Vec<String> = game
+            .lies()
+            .iter()
+            .map(|i| messages.turn(&game.history()[*i]))
+            .collect();
+        match lies.len() {
+            0 => println!("{}", messages.get(Key::NoLies)),
+            n => println!(
+                "{}",
+                messages.format(Key::Lied, &[("count", &n), ("lies", &lies.join(", "))])
+            ),
+        }
+    }
+
+    if game.is_evil() || game.max_lies() > 0 {
+        match game.verify() {
+            Ok(()) => println!(
+                "{}",
+                messages.format(Key::ChecksOut, &[("secret", &game.secret())])
+            ),
+            Err(guess) => println!("{}", messages.format(Key::NotOwnedUp, &[("guess", &guess)])),
+        }
+    }
+
+    if let Some(report) = game.coach_report() {
+        println!("{}", messages.coach_report(&report));
+    }
+
+    finish(
+        &config,
+        &game,
+        &label,
+        &messages,
+        &scores_path,
+        &mut high_scores,
+    );
+}
+
+fn load_scores(config: &Config, messages: &Messages) -> (PathBuf, HighScores) {
+    let path = config
+        .scores_path
+        .clone()
+        .unwrap_or_else(HighScores::default_path);
+
+    let high_scores = match HighScores::load(&path) {
+        Ok(scores) => scores,
+        Err(err) => {
+            warn(
+                messages,
+                messages.format(Key::CouldntReadScores, &[("err", &err)]),
+            );
+            HighScores::default()
+        }
+    };
+    skipped(messages, high_scores.skipped, &path);
+
+    (path, high_scores)
+}
+
+fn show_best(config: &Config, high_scores: &HighScores, label: &str, messages: &Messages) {
+    if let Some(best) = high_scores.best(&player(config), label) {
+        println!(
+            "{}",
+            messages.format(Key::YourBest, &[("label", &label), ("score", &best.score)])
+        );
+    }
+}
+
+/// Saves the game to the player's stats and, if it was won, the score and the high-score table,
+/// whichever kind of game it was.
+fn finish(
+    config: &Config,
+    round: &dyn Round,
+    label: &str,
+    messages: &Messages,
+    scores_path: &Path,
+    high_scores: &mut HighScores,
+) {
+    let player = player(config);
+    let score = round.score();
+    let millis = round.elapsed().as_millis() as u64;
+
+    record_game(
+        config,
+        GameRecord {
+            player: player.clone(),
+            difficulty: label.to_string(),
+            won: round.is_won(),
+            attempts: round.attempts(),
+            score,
+            millis,
+        },
+        messages,
+    );
+
+    if !round.is_won() {
+        return;
+    }
+
+    let (points, row) = match config.timed() {
+        true => (Key::PointsTimed, Key::HighScoreRowTimed),
+        false => (Key::Points, Key::HighScoreRow),
+    };
+    println!(
+        "{}",
+        messages.format(
+            points,
+            &[
+                ("score", &score),
+                ("attempts", &round.attempts()),
+                ("time", &clock::seconds(round.elapsed())),
+            ]
+        )
+    );
+
+    let entry = HighScore {
+        player,
+        difficulty: label.to_string(),
+        score,
+        attempts: round.attempts(),
+        millis,
+    };
+    if high_scores.record(entry) {
+        println!("{}", messages.get(Key::NewHighScore));
+        if let Err(err) = high_scores.save(scores_path) {
+            warn(
+                messages,
+                messages.format(Key::CouldntSaveScores, &[("err", &err)]),
+            );
+        }
+    }
+
+    println!("{}", messages.format(Key::HighScores, &[("label", &label)]));
+    for (i, entry) in high_scores.top(label, 5).iter().enumerate() {
+        // padded before they go in, so the columns still line up in every language
+        println!(
+            "{}",
+            messages.format(
+                row,
+                &[
+                    ("rank", &format!("{:>2}", i + 1)),
+                    ("player", &format!("{:<16}", entry.player)),
+                    ("score", &format!("{:>5}", entry.score)),
+                    ("attempts", &entry.attempts),
+                    ("time", &clock::seconds(Duration::from_millis(entry.millis))),
+                ]
+            )
+        );
+    }
+}
+
+// a transcript that can't be written shouldn't end the game, just stop writing it
+fn log<F>(transcript: &mut Option<Transcript<File>>, messages: &Messages, write: F)
+where
+    F: FnOnce(&mut Transcript<File>) -> io::Result<()>,
+{
+    if let Some(t) = transcript {
+        if let Err(err) = write(t) {
+            warn(
+                messages,
+                messages.format(Key::CouldntWriteTranscript, &[("err", &err)]),
+            );
+            *transcript = None;
         }
     }
 }
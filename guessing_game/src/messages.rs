@@ -0,0 +1,852 @@
+//! Everything the game says to the player, in each language it speaks.
+//!
+//! Each locale is one `match` over [`Key`], so a locale that's missing a message doesn't compile.
+//! What the compiler can't see is a translation that forgets a `{placeholder}`, which is what
+//! [`check`] is for.
+use crate::clock::{self, Timeout};
+use crate::coach::{Advice, Report};
+use crate::config::{ConfigError, Range};
+use crate::game::{Outcome, Turn};
+use crate::hints::{Band, Hint, Trend};
+use crate::input::Rejection;
+use crate::round::Answer;
+use crate::solver;
+use crate::transcript::{Divergence, ReplayError};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// Takes `--lang` values and `LANG`-style tags alike: `es`, `es_ES`, `es_ES.UTF-8`.
+    pub fn parse(tag: &str) -> Result<Locale, ConfigError> {
+        let language = tag.split(['_', '-', '.', '@']).next().unwrap_or_default();
+
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Ok(Locale::En),
+            "es" => Ok(Locale::Es),
+            _ => Err(ConfigError::UnknownLocale(tag.to_string())),
+        }
+    }
+
+    /// The first of `LC_ALL`, `LC_MESSAGES` and `LANG` that's set, like everything else on the
+    /// system. Anything we don't have a catalog for gets English.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|tag| !tag.is_empty())
+            .and_then(|tag| Locale::parse(&tag).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+}
+
+// `Key` and `Key::ALL` come from the one list, so a new key can't be left out of `ALL` and so
+// out of `check`
+macro_rules! keys {
+    ($($key:ident,)*) => {
+        /// One message in the catalog. Placeholders are written `{name}` and filled in by
+        /// [`Messages::format`].
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            /// Every key, in the order they're declared.
+            pub const ALL: &'static [Key] = &[$(Key::$key,)*];
+        }
+    };
+}
+
+keys! {
+    GuessTheNumber,
+    GuessTheCode,
+    Seed,
+    PleaseInput,
+    PleaseInputLimits,
+    AttemptsLeft,
+    TimeToGo,
+    Between,
+    OfDigits,
+    YouGuessed,
+    TooSmall,
+    TooBig,
+    YouWin,
+    SecretIs,
+    Bull,
+    Bulls,
+    Cow,
+    Cows,
+    Cracked,
+    SecretWas,
+    FindAll,
+    FoundOne,
+    ForSecret,
+    FoundSoFar,
+    FoundAll,
+    Advice,
+    AdviceWin,
+    CoachReport,
+    NoMoreInput,
+    OutOfGuesses,
+    OutOfTime,
+    SlowGuess,
+    SlowGame,
+    Empty,
+    NotANumber,
+    Negative,
+    OutOfRange,
+    Overflow,
+    WrongLength,
+    RepeatedDigit,
+    Burning,
+    Hot,
+    Warm,
+    Cold,
+    Warmer,
+    Colder,
+    NoCloser,
+    MayLie,
+    NoLies,
+    Lied,
+    ChecksOut,
+    NotOwnedUp,
+    YourBest,
+    Points,
+    PointsTimed,
+    NewHighScore,
+    HighScores,
+    HighScoreRow,
+    HighScoreRowTimed,
+    StillPossible,
+    Guesses,
+    NoneYet,
+    NotAGuess,
+    Attempts,
+    GuessField,
+    Hosting,
+    TheyWon,
+    TheyLost,
+    TheyLeft,
+    ServingOn,
+    BotGames,
+    StrategyColumn,
+    GamesColumn,
+    WinsColumn,
+    MeanColumn,
+    MaxColumn,
+    AttemptsToWin,
+    NoGamesYet,
+    StatsPlayed,
+    StatsMean,
+    StatsBest,
+    StatsStreak,
+    Replayed,
+    ReplayedDiverged,
+    Diverged,
+    CatalogOk,
+    IsMissing,
+    HasUnknown,
+    Error,
+    Warning,
+    CouldntRead,
+    CouldntOpen,
+    CouldntCreate,
+    ReadFailed,
+    SkippedLines,
+    CouldntSaveStats,
+    CouldntReadScores,
+    CouldntSaveScores,
+    CouldntWriteTranscript,
+    TuiNeedsTerminal,
+    CouldntAccept,
+    EmptyRange,
+    TooWide,
+    UnknownDifficulty,
+    UnknownOption,
+    MissingValue,
+    InvalidNumber,
+    NoAttempts,
+    UnknownStrategy,
+    SecretOutOfRange,
+    Conflict,
+    Unsupported,
+    UnknownLocale,
+    UnknownFormat,
+    UnknownNumberType,
+    UnknownHintPolicy,
+    NotBetween,
+    AtLine,
+    CouldntReadTranscript,
+    MissingStart,
+    RangeIsEmpty,
+}
+
+fn en(key: Key) -> &'static str {
+    match key {
+        Key::GuessTheNumber => "Guess the number!",
+        Key::GuessTheCode => "Guess the code! It's {digits} different digits.",
+        Key::Seed => "Seed: {seed}",
+        Key::PleaseInput => "Please input your guess {what}.",
+        Key::PleaseInputLimits => "Please input your guess {what} ({limits}).",
+        Key::AttemptsLeft => "{left} left",
+        Key::TimeToGo => "{time} to go",
+        Key::Between => "between {low} and {high}",
+        Key::OfDigits => "of {digits} different digits",
+        Key::YouGuessed => "You guessed: {guess}",
+        Key::TooSmall => "Too small!",
+        Key::TooBig => "Too big!",
+        Key::YouWin => "You Win!",
+        Key::SecretIs => "{outcome} The secret is {range}.",
+        Key::Bull => "{count} bull",
+        Key::Bulls => "{count} bulls",
+        Key::Cow => "{count} cow",
+        Key::Cows => "{count} cows",
+        Key::Cracked => "Cracked in {attempts} attempts.",
+        Key::SecretWas => "The secret was {secret}.",
+        Key::FindAll => "Find all {count} secrets!",
+        Key::FoundOne => "Found one!",
+        Key::ForSecret => "#{n}: {outcome}",
+        Key::FoundSoFar => "{found} of {total} found.",
+        Key::FoundAll => "Found them all in {attempts} attempts.",
+        Key::Advice => "{left} still possible. That guess was worth {gained} bits, guessing {midpoint} would have been worth {best}.",
+        Key::AdviceWin => "That guess was worth {gained} bits, guessing {midpoint} would have been worth {best}.",
+        Key::CoachReport => "Coach: {attempts} guesses where binary search needs at most {bound}. You gained {gained} bits where midpoints would have gained {best}, {efficiency}% as efficient.",
+        Key::NoMoreInput => "No more input, the secret was {secret}.",
+        Key::OutOfGuesses => "Out of guesses! The secret was {secret}.",
+        Key::OutOfTime => "Out of time! The secret was {secret}.",
+        Key::SlowGuess => "Too slow! You had {limit} for each guess.",
+        Key::SlowGame => "Too slow! You had {limit} for the whole game.",
+        Key::Empty => "Please type a number.",
+        Key::NotANumber => "'{text}' isn't a number.",
+        Key::Negative => "{guess} is negative, the secret is {range}.",
+        Key::OutOfRange => "{guess} is out of range, the secret is {range}.",
+        Key::Overflow => "{text} is far too big to be a guess.",
+        Key::WrongLength => "That's {got} digits, the code has {expected}.",
+        Key::RepeatedDigit => "{digit} is in there twice, the digits are all different.",
+        Key::Burning => "Burning!",
+        Key::Hot => "Hot.",
+        Key::Warm => "Warm.",
+        Key::Cold => "Cold.",
+        Key::Warmer => "Warmer than last time.",
+        Key::Colder => "Colder than last time.",
+        Key::NoCloser => "No closer than last time.",
+        Key::MayLie => "Careful, up to {lies} of the answers may be lies.",
+        Key::NoLies => "The host told the truth the whole time.",
+        Key::Lied => "The host lied {count} times: {lies}",
+        Key::ChecksOut => "Every other answer checks out against {secret}, no cheating.",
+        Key::NotOwnedUp => "The answer to {guess} wasn't owned up to!",
+        Key::YourBest => "Your best on {label}: {score} points",
+        Key::Points => "{score} points in {attempts} attempts.",
+        Key::PointsTimed => "{score} points in {attempts} attempts and {time}.",
+        Key::NewHighScore => "New high score!",
+        Key::HighScores => "High scores ({label}):",
+        Key::HighScoreRow => "{rank}. {player} {score} points ({attempts} attempts)",
+        Key::HighScoreRowTimed => "{rank}. {player} {score} points ({attempts} attempts, {time})",
+        Key::StillPossible => "still possible: {low}..={high} ({count} numbers)",
+        Key::Guesses => "Guesses:",
+        Key::NoneYet => "none yet",
+        Key::NotAGuess => "(not a guess)",
+        Key::Attempts => "{attempts} attempts",
+        Key::GuessField => "guess: ",
+        Key::Hosting => "Hosting on {addr}, the secret is {secret}. Waiting for a player...",
+        Key::TheyWon => "They got it in {attempts} attempts.",
+        Key::TheyLost => "They ran out of guesses.",
+        Key::TheyLeft => "They left before the game was over.",
+        Key::ServingOn => "Serving games on {url}",
+        Key::BotGames => "{games} games {range}, binary search needs at most {bound} attempts",
+        Key::StrategyColumn => "strategy",
+        Key::GamesColumn => "games",
+        Key::WinsColumn => "wins",
+        Key::MeanColumn => "mean",
+        Key::MaxColumn => "max",
+        Key::AttemptsToWin => "{strategy}: attempts to win",
+        Key::NoGamesYet => "No games played yet.",
+        Key::StatsPlayed => "played {played}, won {won} ({rate}%)",
+        Key::StatsMean => "mean attempts to win: {mean}",
+        Key::StatsBest => "best game: {score} points in {attempts} attempts on {label}",
+        Key::StatsStreak => "streak: {current} now, {longest} at best",
+        Key::Replayed => "{path}: {turns} turns replayed, no divergence",
+        Key::ReplayedDiverged => "{path}: {turns} turns replayed, {diverged} diverged",
+        Key::Diverged => "line {line}: transcript says {expected}, engine says {actual}",
+        Key::CatalogOk => "{keys} messages in {languages} languages, all present and correct",
+        Key::IsMissing => "is missing {names}",
+        Key::HasUnknown => "has unknown {names}",
+        Key::Error => "error: {error}",
+        Key::Warning => "warning: {warning}",
+        Key::CouldntRead => "couldn't read {path}: {err}",
+        Key::CouldntOpen => "couldn't open {path}: {err}",
+        Key::CouldntCreate => "couldn't create {path}: {err}",
+        Key::ReadFailed => "failed to read line: {err}",
+        Key::SkippedLines => "skipped {count} unreadable lines in {path}",
+        Key::CouldntSaveStats => "couldn't save stats: {err}",
+        Key::CouldntReadScores => "couldn't read high scores: {err}",
+        Key::CouldntSaveScores => "couldn't save high scores: {err}",
+        Key::CouldntWriteTranscript => "couldn't write transcript: {err}",
+        Key::TuiNeedsTerminal => "--tui needs a terminal, playing line by line",
+        Key::CouldntAccept => "failed to accept a connection: {err}",
+        Key::EmptyRange => "the range {low}..={high} is empty, --min must not be bigger than --max",
+        Key::TooWide => "the range {low}..={high} is too wide to pick a secret from",
+        Key::UnknownDifficulty => "unknown difficulty '{name}', expected easy, normal or hard",
+        Key::UnknownOption => "unknown option '{option}'",
+        Key::MissingValue => "{option} needs a value",
+        Key::InvalidNumber => "{option} expects a number, got '{value}'",
+        Key::NoAttempts => "--max-attempts must be at least 1",
+        Key::UnknownStrategy => "unknown strategy '{name}', expected {strategies} or all",
+        Key::SecretOutOfRange => "the secret {secret} has to be {range}",
+        Key::Conflict => "{first} and {second} can't be used together",
+        Key::Unsupported => "{option} doesn't work with {game}",
+        Key::UnknownLocale => "unknown language '{tag}', expected en or es",
+        Key::UnknownFormat => "unknown format '{name}', expected text, csv or json",
+        Key::UnknownNumberType => "unknown number type '{name}', expected i64, u64, i128 or decimal",
+        Key::UnknownHintPolicy => "unknown hint policy '{name}', expected off, bands, trend or both",
+        Key::NotBetween => "{option} must be between {low} and {high}",
+        Key::AtLine => "line {line}: {error}",
+        Key::CouldntReadTranscript => "couldn't read transcript: {err}",
+        Key::MissingStart => "transcript doesn't begin with a start record",
+        Key::RangeIsEmpty => "the range is empty",
+    }
+}
+
+fn es(key: Key) -> &'static str {
+    match key {
+        Key::GuessTheNumber => "¡Adivina el número!",
+        Key::GuessTheCode => "¡Adivina el código! Son {digits} cifras distintas.",
+        Key::Seed => "Semilla: {seed}",
+        Key::PleaseInput => "Escribe un número {what}.",
+        Key::PleaseInputLimits => "Escribe un número {what} ({limits}).",
+        Key::AttemptsLeft => "quedan {left}",
+        Key::TimeToGo => "faltan {time}",
+        Key::Between => "entre {low} y {high}",
+        Key::OfDigits => "de {digits} cifras distintas",
+        Key::YouGuessed => "Has dicho: {guess}",
+        Key::TooSmall => "¡Demasiado pequeño!",
+        Key::TooBig => "¡Demasiado grande!",
+        Key::YouWin => "¡Has ganado!",
+        Key::SecretIs => "{outcome} El número secreto está {range}.",
+        Key::Bull => "{count} toro",
+        Key::Bulls => "{count} toros",
+        Key::Cow => "{count} vaca",
+        Key::Cows => "{count} vacas",
+        Key::Cracked => "Descifrado en {attempts} intentos.",
+        Key::SecretWas => "El secreto era {secret}.",
+        Key::FindAll => "¡Encuentra los {count} números secretos!",
+        Key::FoundOne => "¡Encontraste uno!",
+        Key::ForSecret => "n.º {n}: {outcome}",
+        Key::FoundSoFar => "{found} de {total} encontrados.",
+        Key::FoundAll => "Encontrados todos en {attempts} intentos.",
+        Key::Advice => "Aún hay {left} posibles. Ese intento valía {gained} bits, decir {midpoint} habría valido {best}.",
+        Key::AdviceWin => "Ese intento valía {gained} bits, decir {midpoint} habría valido {best}.",
+        Key::CoachReport => "Entrenador: {attempts} intentos donde la búsqueda binaria necesita como mucho {bound}. Has ganado {gained} bits donde los puntos medios habrían ganado {best}, una eficiencia del {efficiency}%.",
+        Key::NoMoreInput => "No hay más entrada, el secreto era {secret}.",
+        Key::OutOfGuesses => "¡Sin intentos! El secreto era {secret}.",
+        Key::OutOfTime => "¡Se acabó el tiempo! El secreto era {secret}.",
+        Key::SlowGuess => "¡Demasiado lento! Tenías {limit} para cada intento.",
+        Key::SlowGame => "¡Demasiado lento! Tenías {limit} para toda la partida.",
+        Key::Empty => "Escribe un número, por favor.",
+        Key::NotANumber => "'{text}' no es un número.",
+        Key::Negative => "{guess} es negativo, el número secreto está {range}.",
+        Key::OutOfRange => "{guess} se sale del rango, el número secreto está {range}.",
+        Key::Overflow => "{text} es demasiado grande para ser un intento.",
+        Key::WrongLength => "Eso son {got} cifras, el código tiene {expected}.",
+        Key::RepeatedDigit => "El {digit} sale dos veces, las cifras son todas distintas.",
+        Key::Burning => "¡Te quemas!",
+        Key::Hot => "Caliente.",
+        Key::Warm => "Templado.",
+        Key::Cold => "Frío.",
+        Key::Warmer => "Más caliente que la vez anterior.",
+        Key::Colder => "Más frío que la vez anterior.",
+        Key::NoCloser => "No más cerca que la vez anterior.",
+        Key::MayLie => "Cuidado, hasta {lies} de las respuestas pueden ser mentira.",
+        Key::NoLies => "El anfitrión ha dicho la verdad todo el tiempo.",
+        Key::Lied => "El anfitrión ha mentido {count} veces: {lies}",
+        Key::ChecksOut => "Todas las demás respuestas cuadran con {secret}, sin trampas.",
+        Key::NotOwnedUp => "¡La respuesta a {guess} no se ha reconocido como mentira!",
+        Key::YourBest => "Tu mejor marca en {label}: {score} puntos",
+        Key::Points => "{score} puntos en {attempts} intentos.",
+        Key::PointsTimed => "{score} puntos en {attempts} intentos y {time}.",
+        Key::NewHighScore => "¡Nuevo récord!",
+        Key::HighScores => "Récords ({label}):",
+        Key::HighScoreRow => "{rank}. {player} {score} puntos ({attempts} intentos)",
+        Key::HighScoreRowTimed => "{rank}. {player} {score} puntos ({attempts} intentos, {time})",
+        Key::StillPossible => "aún posible: {low}..={high} ({count} números)",
+        Key::Guesses => "Intentos:",
+        Key::NoneYet => "ninguno todavía",
+        Key::NotAGuess => "(no es un intento)",
+        Key::Attempts => "{attempts} intentos",
+        Key::GuessField => "intento: ",
+        Key::Hosting => "Partida en {addr}, el número secreto es {secret}. Esperando a un jugador...",
+        Key::TheyWon => "Lo ha acertado en {attempts} intentos.",
+        Key::TheyLost => "Se ha quedado sin intentos.",
+        Key::TheyLeft => "Se ha ido antes de acabar la partida.",
+        Key::ServingOn => "Sirviendo partidas en {url}",
+        Key::BotGames => "{games} partidas {range}, la búsqueda binaria necesita como mucho {bound} intentos",
+        Key::StrategyColumn => "estrategia",
+        Key::GamesColumn => "partidas",
+        Key::WinsColumn => "victorias",
+        Key::MeanColumn => "media",
+        Key::MaxColumn => "máx",
+        Key::AttemptsToWin => "{strategy}: intentos hasta ganar",
+        Key::NoGamesYet => "Todavía no se ha jugado ninguna partida.",
+        Key::StatsPlayed => "jugadas {played}, ganadas {won} ({rate}%)",
+        Key::StatsMean => "media de intentos para ganar: {mean}",
+        Key::StatsBest => "mejor partida: {score} puntos en {attempts} intentos en {label}",
+        Key::StatsStreak => "racha: {current} ahora, {longest} como máximo",
+        Key::Replayed => "{path}: {turns} turnos repetidos, sin divergencias",
+        Key::ReplayedDiverged => "{path}: {turns} turnos repetidos, {diverged} con divergencias",
+        Key::Diverged => "línea {line}: la transcripción dice {expected}, el motor dice {actual}",
+        Key::CatalogOk => "{keys} mensajes en {languages} idiomas, todos presentes y correctos",
+        Key::IsMissing => "no tiene {names}",
+        Key::HasUnknown => "tiene {names}, que no existen",
+        Key::Error => "error: {error}",
+        Key::Warning => "aviso: {warning}",
+        Key::CouldntRead => "no se ha podido leer {path}: {err}",
+        Key::CouldntOpen => "no se ha podido abrir {path}: {err}",
+        Key::CouldntCreate => "no se ha podido crear {path}: {err}",
+        Key::ReadFailed => "no se ha podido leer la línea: {err}",
+        Key::SkippedLines => "se han saltado {count} líneas ilegibles en {path}",
+        Key::CouldntSaveStats => "no se han podido guardar las estadísticas: {err}",
+        Key::CouldntReadScores => "no se han podido leer los récords: {err}",
+        Key::CouldntSaveScores => "no se han podido guardar los récords: {err}",
+        Key::CouldntWriteTranscript => "no se ha podido escribir la transcripción: {err}",
+        Key::TuiNeedsTerminal => "--tui necesita un terminal, se juega línea a línea",
+        Key::CouldntAccept => "no se ha podido aceptar una conexión: {err}",
+        Key::EmptyRange => "el rango {low}..={high} está vacío, --min no puede ser mayor que --max",
+        Key::TooWide => "el rango {low}..={high} es demasiado amplio para elegir un número secreto",
+        Key::UnknownDifficulty => "dificultad desconocida '{name}', se esperaba easy, normal o hard",
+        Key::UnknownOption => "opción desconocida '{option}'",
+        Key::MissingValue => "{option} necesita un valor",
+        Key::InvalidNumber => "{option} espera un número, no '{value}'",
+        Key::NoAttempts => "--max-attempts tiene que ser al menos 1",
+        Key::UnknownStrategy => "estrategia desconocida '{name}', se esperaba {strategies} o all",
+        Key::SecretOutOfRange => "el número secreto {secret} tiene que estar {range}",
+        Key::Conflict => "{first} y {second} no se pueden usar a la vez",
+        Key::Unsupported => "{option} no funciona con {game}",
+        Key::UnknownLocale => "idioma desconocido '{tag}', se esperaba en o es",
+        Key::UnknownFormat => "formato desconocido '{name}', se esperaba text, csv o json",
+        Key::UnknownNumberType => "tipo de número desconocido '{name}', se esperaba i64, u64, i128 o decimal",
+        Key::UnknownHintPolicy => "tipo de pista desconocido '{name}', se esperaba off, bands, trend o both",
+        Key::NotBetween => "{option} tiene que estar entre {low} y {high}",
+        Key::AtLine => "línea {line}: {error}",
+        Key::CouldntReadTranscript => "no se ha podido leer la transcripción: {err}",
+        Key::MissingStart => "la transcripción no empieza con un registro de inicio",
+        Key::RangeIsEmpty => "el rango está vacío",
+    }
+}
+
+/// The catalog for one locale, with helpers for the game's own types so callers don't each
+/// have to pick the right key. The default is English, which is also what those types' `Display`
+/// impls say.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Messages {
+    locale: Locale,
+}
+
+impl Messages {
+    pub fn new(locale: Locale) -> Messages {
+        Messages { locale }
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// The message as it's written in the catalog, placeholders and all.
+    pub fn get(&self, key: Key) -> &'static str {
+        match self.locale {
+            Locale::En => en(key),
+            Locale::Es => es(key),
+        }
+    }
+
+    /// The message with each `{name}` replaced by its value. It's one pass through the message,
+    /// so a value with braces in it, a player called `{score}` say, comes out as it is.
+    pub fn format(&self, key: Key, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut message = String::new();
+        let mut rest = self.get(key);
+
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let value = rest.find('}').and_then(|end| {
+                let (_, value) = args.iter().find(|(name, _)| *name == &rest[1..end])?;
+                Some((value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    message.push_str(&value.to_string());
+                    rest = &rest[end + 1..];
+                }
+                // not a placeholder we were given, so it's left as it is
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+
+        message.push_str(rest);
+        message
+    }
+
+    pub fn outcome(&self, outcome: Outcome) -> &'static str {
+        self.get(match outcome {
+            Outcome::TooSmall => Key::TooSmall,
+            Outcome::TooBig => Key::TooBig,
+            Outcome::Win => Key::YouWin,
+        })
+    }
+
+    pub fn answer(&self, answer: &Answer) -> String {
+        match *answer {
+            Answer::Number(outcome) => self.outcome(outcome).to_string(),
+            Answer::Nearest(Outcome::Win) => self.get(Key::FoundOne).to_string(),
+            Answer::Nearest(outcome) => self.outcome(outcome).to_string(),
+            Answer::Each(ref outcomes) => {
+                let each: Vec<String> = outcomes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, outcome)| {
+                        self.format(
+                            Key::ForSecret,
+                            &[("n", &(i + 1)), ("outcome", &self.outcome(*outcome))],
+                        )
+                    })
+                    .collect();
+                each.join(" ")
+            }
+            Answer::Code { bulls, cows } => {
+                let bulls = match bulls {
+                    1 => self.format(Key::Bull, &[("count", &bulls)]),
+                    _ => self.format(Key::Bulls, &[("count", &bulls)]),
+                };
+                let cows = match cows {
+                    1 => self.format(Key::Cow, &[("count", &cows)]),
+                    _ => self.format(Key::Cows, &[("count", &cows)]),
+                };
+                format!("{bulls}, {cows}.")
+            }
+        }
+    }
+
+    /// A line of a game's history: the guess and its answer, or the input and why it was turned
+    /// down.
+    pub fn turn<N: fmt::Display>(&self, turn: &Turn<N>) -> String {
+        match turn {
+            Turn::Guess(guess, outcome) => format!("{guess}: {}", self.outcome(*outcome)),
+            Turn::Rejected(line, rejection) => format!("'{line}': {}", self.rejection(rejection)),
+        }
+    }
+
+    /// "between 1 and 100", for the middle of a sentence.
+    pub fn range(&self, range: Range) -> String {
+        self.between(&range.low(), &range.high())
+    }
+
+    /// [`Messages::range`] for numbers that aren't `i64`s.
+    pub fn between(&self, low: &dyn fmt::Display, high: &dyn fmt::Display) -> String {
+        self.format(Key::Between, &[("low", low), ("high", high)])
+    }
+
+    pub fn timeout(&self, timeout: Timeout) -> String {
+        match timeout {
+            Timeout::Guess(limit) => {
+                self.format(Key::SlowGuess, &[("limit", &clock::clock(limit))])
+            }
+            Timeout::Game(limit) => self.format(Key::SlowGame, &[("limit", &clock::clock(limit))]),
+        }
+    }
+
+    pub fn rejection(&self, rejection: &Rejection) -> String {
+        match rejection {
+            Rejection::Empty => self.get(Key::Empty).to_string(),
+            Rejection::NotANumber(text) => self.format(Key::NotANumber, &[("text", text)]),
+            Rejection::Negative(guess, range) => self.format(
+                Key::Negative,
+                &[("guess", guess), ("range", &self.range(*range))],
+            ),
+            Rejection::OutOfRange(guess, range) => self.format(
+                Key::OutOfRange,
+                &[("guess", guess), ("range", &self.range(*range))],
+            ),
+            Rejection::Outside { guess, low, high } => self.format(
+                Key::OutOfRange,
+                &[("guess", guess), ("range", &self.between(low, high))],
+            ),
+            Rejection::Overflow(text) => self.format(Key::Overflow, &[("text", text)]),
+            Rejection::WrongLength { got, expected } => {
+                self.format(Key::WrongLength, &[("got", got), ("expected", expected)])
+            }
+            Rejection::RepeatedDigit(digit) => self.format(Key::RepeatedDigit, &[("digit", digit)]),
+            Rejection::TimedOut(timeout) => self.timeout(*timeout),
+        }
+    }
+
+    /// `won` leaves out how many numbers are left, there's no need.
+    pub fn advice(&self, advice: &Advice, won: bool) -> String {
+        let bits = |bits: f64| format!("{bits:.2}");
+        let args: [(&str, &dyn fmt::Display); 4] = [
+            ("left", &advice.after),
+            ("gained", &bits(advice.gained)),
+            ("midpoint", &advice.midpoint),
+            ("best", &bits(advice.best)),
+        ];
+
+        match won {
+            true => self.format(Key::AdviceWin, &args),
+            false => self.format(Key::Advice, &args),
+        }
+    }
+
+    pub fn coach_report(&self, report: &Report) -> String {
+        self.format(
+            Key::CoachReport,
+            &[
+                ("attempts", &report.attempts),
+                ("bound", &report.binary_search),
+                ("gained", &format!("{:.2}", report.gained)),
+                ("best", &format!("{:.2}", report.best)),
+                ("efficiency", &format!("{:.0}", report.efficiency())),
+            ],
+        )
+    }
+
+    pub fn hint(&self, hint: Hint) -> String {
+        let band = hint.band.map(|band| {
+            self.get(match band {
+                Band::Burning => Key::Burning,
+                Band::Hot => Key::Hot,
+                Band::Warm => Key::Warm,
+                Band::Cold => Key::Cold,
+            })
+        });
+        let trend = hint.trend.map(|trend| {
+            self.get(match trend {
+                Trend::Warmer => Key::Warmer,
+                Trend::Colder => Key::Colder,
+                Trend::Same => Key::NoCloser,
+            })
+        });
+
+        band.into_iter().chain(trend).collect::<Vec<_>>().join(" ")
+    }
+
+    pub fn config_error(&self, err: &ConfigError) -> String {
+        match err {
+            ConfigError::InvertedRange { low, high } => {
+                self.format(Key::EmptyRange, &[("low", low), ("high", high)])
+            }
+            ConfigError::EmptyRange { low, high } => {
+                self.format(Key::EmptyRange, &[("low", low), ("high", high)])
+            }
+            ConfigError::TooWide { low, high } => {
+                self.format(Key::TooWide, &[("low", low), ("high", high)])
+            }
+            ConfigError::UnknownDifficulty(name) => {
+                self.format(Key::UnknownDifficulty, &[("name", name)])
+            }
+            ConfigError::UnknownOption(option) => {
+                self.format(Key::UnknownOption, &[("option", option)])
+            }
+            ConfigError::MissingValue(option) => {
+                self.format(Key::MissingValue, &[("option", option)])
+            }
+            ConfigError::InvalidNumber { option, value } => {
+                self.format(Key::InvalidNumber, &[("option", option), ("value", value)])
+            }
+            ConfigError::NoAttempts => self.get(Key::NoAttempts).to_string(),
+            ConfigError::UnknownStrategy(name) => self.format(
+                Key::UnknownStrategy,
+                &[
+                    ("name", name),
+                    ("strategies", &solver::STRATEGIES.join(", ")),
+                ],
+            ),
+            ConfigError::SecretOutOfRange { secret, range } => self.format(
+                Key::SecretOutOfRange,
+                &[("secret", secret), ("range", &self.range(*range))],
+            ),
+            ConfigError::Conflict(first, second) => {
+                self.format(Key::Conflict, &[("first", first), ("second", second)])
+            }
+            ConfigError::Unsupported { option, game } => {
+                self.format(Key::Unsupported, &[("option", option), ("game", game)])
+            }
+            ConfigError::UnknownLocale(tag) => self.format(Key::UnknownLocale, &[("tag", tag)]),
+            ConfigError::UnknownFormat(name) => self.format(Key::UnknownFormat, &[("name", name)]),
+            ConfigError::UnknownNumberType(name) => {
+                self.format(Key::UnknownNumberType, &[("name", name)])
+            }
+            ConfigError::UnknownHintPolicy(name) => {
+                self.format(Key::UnknownHintPolicy, &[("name", name)])
+            }
+            ConfigError::NotBetween { option, low, high } => self.format(
+                Key::NotBetween,
+                &[("option", option), ("low", low), ("high", high)],
+            ),
+        }
+    }
+
+    pub fn replay_error(&self, err: &ReplayError) -> String {
+        let at = |line: &usize, error: &dyn fmt::Display| {
+            self.format(Key::AtLine, &[("line", line), ("error", error)])
+        };
+
+        match err {
+            ReplayError::Io(err) => self.format(Key::CouldntReadTranscript, &[("err", err)]),
+            ReplayError::Json { line, err } => at(line, err),
+            ReplayError::MissingStart => self.get(Key::MissingStart).to_string(),
+            ReplayError::BadRange { line } => at(line, &self.get(Key::RangeIsEmpty)),
+            ReplayError::BadNumbers { line, err } => at(line, &self.config_error(err)),
+        }
+    }
+
+    pub fn divergence(&self, divergence: &Divergence) -> String {
+        self.format(
+            Key::Diverged,
+            &[
+                ("line", &divergence.line),
+                ("expected", &divergence.expected),
+                ("actual", &divergence.actual),
+            ],
+        )
+    }
+
+    pub fn problem(&self, problem: &Problem) -> String {
+        let names = |names: &[String]| format!("{{{}}}", names.join("}, {"));
+
+        let mut message = format!("{}: {:?}", problem.locale.name(), problem.key);
+        if !problem.missing.is_empty() {
+            message.push(' ');
+            message += &self.format(Key::IsMissing, &[("names", &names(&problem.missing))]);
+        }
+        if !problem.unknown.is_empty() {
+            message.push(' ');
+            message += &self.format(Key::HasUnknown, &[("names", &names(&problem.unknown))]);
+        }
+        message
+    }
+}
+
+/// A message whose placeholders don't match the English one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub locale: Locale,
+    pub key: Key,
+    pub missing: Vec<String>,
+    pub unknown: Vec<String>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().problem(self))
+    }
+}
+
+/// Goes through every key in every locale, checking it has the same placeholders as English.
+/// English is the reference, so it's where new keys get added first.
+pub fn check() -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    for locale in Locale::ALL {
+        let messages = Messages::new(locale);
+        for &key in Key::ALL {
+            let expected = placeholders(en(key));
+            let found = placeholders(messages.get(key));
+
+            let missing: Vec<String> = expected.difference(&found).cloned().collect();
+            let unknown: Vec<String> = found.difference(&expected).cloned().collect();
+            if !missing.is_empty() || !unknown.is_empty() {
+                problems.push(Problem {
+                    locale,
+                    key,
+                    missing,
+                    unknown,
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+fn placeholders(message: &str) -> std::collections::BTreeSet<String> {
+    message
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_translation_has_the_right_placeholders() {
+        assert_eq!(check(), []);
+    }
+
+    #[test]
+    fn every_key_is_listed_once() {
+        // a fieldless enum counts up from 0 in the order it's declared, so this is every key
+        for (i, key) in Key::ALL.iter().enumerate() {
+            assert_eq!(*key as usize, i, "{key:?} is out of place");
+        }
+    }
+
+    #[test]
+    fn values_are_filled_in_once() {
+        let messages = Messages::new(Locale::En);
+        let row = messages.format(
+            Key::HighScoreRow,
+            &[
+                ("rank", &1),
+                ("player", &"{score}"),
+                ("score", &664),
+                ("attempts", &"{rank}"),
+            ],
+        );
+
+        assert_eq!(row, "1. {score} 664 points ({rank} attempts)");
+    }
+
+    #[test]
+    fn placeholders_without_a_value_are_left_alone() {
+        let messages = Messages::new(Locale::En);
+
+        assert_eq!(
+            messages.format(Key::Between, &[("low", &1)]),
+            "between 1 and {high}"
+        );
+        assert_eq!(messages.format(Key::Seed, &[]), "Seed: {seed}");
+    }
+
+    #[test]
+    fn english_is_what_display_says() {
+        let rejection = Rejection::NotANumber(String::from("abc"));
+        let messages = Messages::new(Locale::En);
+
+        assert_eq!(rejection.to_string(), messages.rejection(&rejection));
+        assert_eq!(
+            Outcome::TooBig.to_string(),
+            messages.outcome(Outcome::TooBig)
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Two players over TCP: one hosts the game and knows the secret, the other joins and guesses.
+//!
+//! The protocol is a line at a time. The host opens with `RANGE <low> <high>`, then answers each
+//! line the guesser sends with one of
+//!
+//! - `OK <outcome>`, where the outcome is the usual "Too small!", "Too big!" or "You Win!"
+//! - `REJECTED <json>` for input that wasn't a guess, the [`Rejection`] as JSON so that the
+//!   guesser can put it in their own language
+//! - `LOST <secret> <answer>` instead of either of those when it used up the last attempt, with
+//!   the `OK` or `REJECTED` it would otherwise have got
+//!
+//! and hangs up once the game is over. A guesser can send `QUIT` to leave early.
+use crate::config::Range;
+use crate::game::{Game, Outcome};
+use crate::input::{self, Rejection};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What came back from the host for one guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Outcome(Outcome),
+    Rejected(Rejection),
+    /// That was the last attempt. `last` is what it got, the same as the other two.
+    Lost {
+        secret: i64,
+        last: Result<Outcome, Rejection>,
+    },
+}
+
+/// Waits up to `timeout` for someone to connect, then plays `game` with them until it's over or
+/// they leave. The game comes back either way, [`Game::is_over`] says which.
+pub fn host(listener: &TcpListener, game: &mut Game, timeout: Duration) -> io::Result<()> {
+    let stream = accept(listener, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let range = game.range();
+    writeln!(writer, "RANGE {} {}", range.low(), range.high())?;
+
+    while !game.is_over() {
+        let line = match input::read_line(&mut reader).map_err(timed_out)? {
+            Some(line) => line,
+            // they hung up
+            None => return Ok(()),
+        };
+
+        if line.trim() == "QUIT" {
+            return Ok(());
+        }
+
+        let answer = match game.submit(&line) {
+            Ok(outcome) => format!("OK {outcome}"),
+            Err(rejection) => {
+                let why = serde_json::to_string(&rejection).unwrap_or_default();
+                format!("REJECTED {why}")
+            }
+        };
+
+        match game.is_lost() {
+            true => writeln!(writer, "LOST {} {answer}", game.secret())?,
+            false => writeln!(writer, "{answer}")?,
+        }
+    }
+
+    Ok(())
+}
+
+// `TcpListener` has no accept timeout of its own, so poll it
+fn accept(listener: &TcpListener, timeout: Duration) -> io::Result<TcpStream> {
+    listener.set_nonblocking(true)?;
+    let deadline = Instant::now() + timeout;
+
+    let stream = loop {
+        match listener.accept() {
+            Ok((stream, _)) => break stream,
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(io::Error::new(ErrorKind::TimedOut, "nobody joined in time"));
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            Err(err) => return Err(err),
+        }
+    };
+
+    listener.set_nonblocking(false)?;
+    stream.set_nonblocking(false)?;
+    Ok(stream)
+}
+
+// a read timeout shows up as `WouldBlock` on unix and `TimedOut` on windows
+fn timed_out(err: io::Error) -> io::Error {
+    match err.kind() {
+        ErrorKind::WouldBlock => io::Error::new(ErrorKind::TimedOut, "timed out waiting"),
+        _ => err,
+    }
+}
+
+/// The guessing end of a networked game.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    range: Range,
+}
+
+impl Client {
+    pub fn connect<A: ToSocketAddrs>(addr: A, timeout: Duration) -> io::Result<Client> {
+        let mut last_err = io::Error::new(ErrorKind::InvalidInput, "no address to connect to");
+
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => return Client::handshake(stream, timeout),
+                Err(err) => last_err = err,
+            }
+        }
+
+        Err(last_err)
+    }
+
+    fn handshake(stream: TcpStream, timeout: Duration) -> io::Result<Client> {
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let line = read_reply(&mut reader)?;
+
+        let range = line
+            .strip_prefix("RANGE ")
+            .and_then(|rest| rest.split_once(' '))
+            .and_then(|(low, high)| Some((low.parse().ok()?, high.parse().ok()?)))
+            .and_then(|(low, high)| Range::new(low, high).ok())
+            .ok_or_else(|| protocol_error(&line))?;
+
+        Ok(Client {
+            reader,
+            writer: stream,
+            range,
+        })
+    }
+
+    pub fn range(&self) -> Range {
+        self.range
+    }
+
+    /// Sends one line of input and waits for the host's answer.
+    pub fn guess(&mut self, line: &str) -> io::Result<Reply> {
+        writeln!(self.writer, "{}", line.trim())?;
+
+        let reply = read_reply(&mut self.reader)?;
+        let parsed = match reply.strip_prefix("LOST ") {
+            Some(rest) => rest.split_once(' ').and_then(|(secret, answer)| {
+                Some(Reply::Lost {
+                    secret: secret.parse().ok()?,
+                    last: parse_answer(answer)?,
+                })
+            }),
+            None => parse_answer(&reply).map(|answer| match answer {
+                Ok(outcome) => Reply::Outcome(outcome),
+                Err(rejection) => Reply::Rejected(rejection),
+            }),
+        };
+
+        parsed.ok_or_else(|| protocol_error(&reply))
+    }
+
+    pub fn quit(mut self) -> io::Result<()> {
+        writeln!(self.writer, "QUIT")
+    }
+}
+
+// `OK <outcome>` or `REJECTED <json>`
+fn parse_answer(line: &str) -> Option<Result<Outcome, Rejection>> {
+    let (kind, rest) = line.split_once(' ')?;
+
+    match kind {
+        "OK" => rest.parse().ok().map(Ok),
+        "REJECTED" => serde_json::from_str(rest).ok().map(Err),
+        _ => None,
+    }
+}
+
+fn read_reply<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    match input::read_line(reader).map_err(timed_out)? {
+        Some(line) => Ok(line.trim_end().to_string()),
+        None => Err(io::Error::new(ErrorKind::UnexpectedEof, "the host hung up")),
+    }
+}
+
+fn protocol_error(line: &str) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("unexpected message from host: '{line}'"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::thread::JoinHandle;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // a host on a free port with 42 as the secret, and a client joined to it
+    fn hosted(config: Config) -> (Client, JoinHandle<io::Result<Game>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let host = thread::spawn(move || {
+            let mut game = Game::new(&config, 42);
+            host(&listener, &mut game, TIMEOUT).map(|()| game)
+        });
+
+        (Client::connect(addr, TIMEOUT).unwrap(), host)
+    }
+
+    #[test]
+    fn a_win() {
+        let (mut client, host) = hosted(Config::default());
+        assert_eq!(client.range(), Range::default());
+
+        assert_eq!(client.guess("50").unwrap(), Reply::Outcome(Outcome::TooBig));
+        assert_eq!(
+            client.guess("abc").unwrap(),
+            Reply::Rejected(Rejection::NotANumber(String::from("abc")))
+        );
+        assert_eq!(client.guess("42").unwrap(), Reply::Outcome(Outcome::Win));
+
+        let game = host.join().unwrap().unwrap();
+        assert!(game.is_won());
+        assert_eq!(game.attempts(), 2);
+    }
+
+    #[test]
+    fn the_last_guess_comes_back_with_the_secret() {
+        let config = Config {
+            max_attempts: Some(2),
+            ..Config::default()
+        };
+        let (mut client, host) = hosted(config);
+
+        assert_eq!(client.guess("50").unwrap(), Reply::Outcome(Outcome::TooBig));
+        assert_eq!(
+            client.guess("10").unwrap(),
+            Reply::Lost {
+                secret: 42,
+                last: Ok(Outcome::TooSmall)
+            }
+        );
+
+        assert!(host.join().unwrap().unwrap().is_lost());
+    }
+
+    #[test]
+    fn quitting_leaves_the_game_unfinished() {
+        let (mut client, host) = hosted(Config::default());
+
+        client.guess("50").unwrap();
+        client.quit().unwrap();
+
+        let game = host.join().unwrap().unwrap();
+        assert!(!game.is_over());
+        assert_eq!(game.attempts(), 1);
+    }
+
+    #[test]
+    fn hanging_up_mid_game_is_not_an_error() {
+        let (mut client, host) = hosted(Config::default());
+
+        client.guess("50").unwrap();
+        drop(client);
+
+        let game = host.join().unwrap().unwrap();
+        assert!(!game.is_over());
+        assert_eq!(game.attempts(), 1);
+    }
+}
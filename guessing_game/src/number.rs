@@ -0,0 +1,386 @@
+//! The number game for any kind of number: the `i64` the classic game uses, integers too big for
+//! it, and decimals that count as a win when they're close enough.
+use crate::clock::Clock;
+use crate::config::{Config, ConfigError};
+use crate::game::{Outcome, Turn};
+use crate::input::Rejection;
+use crate::messages::{Key, Messages};
+use crate::round::{Answer, GameKind, Progress, Round, Secret};
+use rand::distributions::uniform::SampleUniform;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::IntErrorKind;
+
+/// Something a secret can be.
+pub trait Number:
+    Copy + PartialOrd + SampleUniform + fmt::Display + fmt::Debug + Send + 'static
+{
+    /// Reads a guess, or says why it isn't one. Range checks are the game's job.
+    fn parse(text: &str) -> Result<Self, Rejection>;
+
+    /// Where `self` is relative to `secret`, counting anything within `tolerance` of it as
+    /// equal. Defined for every pair, even for floats, so every guess gets an answer.
+    fn compare(&self, secret: &Self, tolerance: Self) -> Ordering;
+
+    /// No tolerance at all, what integers want.
+    fn zero() -> Self;
+
+    /// Whether a secret can be picked from `low..=high`. Always, for integers.
+    fn pickable(_low: Self, _high: Self) -> bool {
+        true
+    }
+
+    /// Roughly how many secrets in `low..=high` can be told apart, allowing for the tolerance.
+    /// Only used for scoring, so `f64` is close enough.
+    fn possibilities(low: Self, high: Self, tolerance: Self) -> f64;
+}
+
+/// The answer to `guess`, given `secret`.
+pub fn outcome<N: Number>(guess: N, secret: N, tolerance: N) -> Outcome {
+    Outcome::from(guess.compare(&secret, tolerance))
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            fn parse(text: &str) -> Result<$t, Rejection> {
+                text.parse().map_err(|err: std::num::ParseIntError| match err.kind() {
+                    IntErrorKind::Empty => Rejection::Empty,
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                        Rejection::Overflow(text.to_string())
+                    }
+                    _ => Rejection::NotANumber(text.to_string()),
+                })
+            }
+
+            fn compare(&self, secret: &$t, tolerance: $t) -> Ordering {
+                // `abs_diff` can't overflow, even across the whole of an `i128`
+                match self.abs_diff(*secret) <= tolerance.abs_diff(0) {
+                    true => Ordering::Equal,
+                    false => self.cmp(secret),
+                }
+            }
+
+            fn zero() -> $t {
+                0
+            }
+
+            fn possibilities(low: $t, high: $t, tolerance: $t) -> f64 {
+                let window = 2.0 * tolerance.abs_diff(0) as f64 + 1.0;
+                (high.abs_diff(low) as f64 + 1.0) / window
+            }
+        }
+    )*};
+}
+
+integer!(i64, u64, i128);
+
+impl Number for f64 {
+    fn parse(text: &str) -> Result<f64, Rejection> {
+        if text.is_empty() {
+            return Err(Rejection::Empty);
+        }
+
+        match text.parse::<f64>() {
+            // "inf" and "NaN" parse, but they aren't guesses anyone means
+            Ok(n) if n.is_nan() => Err(Rejection::NotANumber(text.to_string())),
+            Ok(n) if n.is_infinite() => Err(Rejection::Overflow(text.to_string())),
+            Ok(n) => Ok(n),
+            Err(_) => Err(Rejection::NotANumber(text.to_string())),
+        }
+    }
+
+    fn compare(&self, secret: &f64, tolerance: f64) -> Ordering {
+        match (self - secret).abs() <= tolerance.abs() {
+            true => Ordering::Equal,
+            false => self.total_cmp(secret),
+        }
+    }
+
+    fn zero() -> f64 {
+        0.0
+    }
+
+    // rand works out `high - low`, which for the widest ranges is bigger than an `f64` can hold
+    fn pickable(low: f64, high: f64) -> bool {
+        (high - low).is_finite()
+    }
+
+    // with no tolerance, about how many floats there are between the two
+    fn possibilities(low: f64, high: f64, tolerance: f64) -> f64 {
+        let step = f64::EPSILON * low.abs().max(high.abs()).max(1.0);
+        (high - low) / (2.0 * tolerance.abs()).max(step)
+    }
+}
+
+/// A numbers game as the command line has it, and as a transcript records it: the type by name,
+/// with the bounds and tolerance as text since they don't all fit in an `i64`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Setup {
+    /// `i64`, `u64`, `i128` or `decimal`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub min: String,
+    pub max: String,
+    pub tolerance: String,
+}
+
+impl Setup {
+    /// Fills in what's left out: `1..=100` for integers and `0..=1` for decimals, with no
+    /// tolerance.
+    pub fn new(
+        kind: &str,
+        min: Option<String>,
+        max: Option<String>,
+        tolerance: Option<String>,
+    ) -> Result<Setup, ConfigError> {
+        let (low, high) = match kind {
+            "i64" | "u64" | "i128" => ("1", "100"),
+            "decimal" => ("0", "1"),
+            _ => return Err(ConfigError::UnknownNumberType(kind.to_string())),
+        };
+        let or = |value: Option<String>, default: &str| {
+            value.map_or_else(|| default.to_string(), |v| v.trim().to_string())
+        };
+
+        Ok(Setup {
+            kind: kind.to_string(),
+            min: or(min, low),
+            max: or(max, high),
+            tolerance: or(tolerance, "0"),
+        })
+    }
+
+    /// What high scores are filed under, e.g. `decimal 0..=1 ±0.01`.
+    pub fn label(&self) -> String {
+        let label = format!("{} {}..={}", self.kind, self.min, self.max);
+        match self.tolerance.parse::<f64>() {
+            Ok(0.0) => label,
+            _ => format!("{label} ±{}", self.tolerance),
+        }
+    }
+
+    /// The game, with its secret picked from `seed` and timed against `clock`.
+    pub fn game(
+        &self,
+        config: &Config,
+        seed: u64,
+        clock: Box<dyn Clock>,
+    ) -> Result<Box<dyn Round>, ConfigError> {
+        match self.kind.as_str() {
+            "i64" => self.build::<i64>(config, seed, clock),
+            "u64" => self.build::<u64>(config, seed, clock),
+            "i128" => self.build::<i128>(config, seed, clock),
+            "decimal" => self.build::<f64>(config, seed, clock),
+            kind => Err(ConfigError::UnknownNumberType(kind.to_string())),
+        }
+    }
+
+    fn build<N: Number>(
+        &self,
+        config: &Config,
+        seed: u64,
+        clock: Box<dyn Clock>,
+    ) -> Result<Box<dyn Round>, ConfigError> {
+        let parse = |option: &str, value: &str| {
+            N::parse(value).map_err(|_| ConfigError::InvalidNumber {
+                option: option.to_string(),
+                value: value.to_string(),
+            })
+        };
+        let low = parse("--min", &self.min)?;
+        let high = parse("--max", &self.max)?;
+        let tolerance = parse("--tolerance", &self.tolerance)?;
+        if low > high {
+            return Err(ConfigError::EmptyRange {
+                low: self.min.clone(),
+                high: self.max.clone(),
+            });
+        }
+        if !N::pickable(low, high) {
+            return Err(ConfigError::TooWide {
+                low: self.min.clone(),
+                high: self.max.clone(),
+            });
+        }
+
+        let game = NumericGame::seeded(config, low, high, tolerance, seed).with_clock(clock);
+        Ok(Box::new(game))
+    }
+}
+
+/// The number game over any [`Number`]. It's the plain game: limits on attempts and time, but
+/// none of the evil host, lies or hints, which lean on there being a next number up. The
+/// attempts and time limits are kept the same way [`crate::Game`] keeps them, in a [`Progress`].
+#[derive(Debug)]
+pub struct NumericGame<N: Number> {
+    secret: N,
+    low: N,
+    high: N,
+    tolerance: N,
+    progress: Progress,
+    history: Vec<Turn<N>>,
+}
+
+impl<N: Number> NumericGame<N> {
+    /// Takes the attempt and time limits from `config`, but the range from `low` and `high`,
+    /// since `config` only knows about `i64`s.
+    pub fn new(config: &Config, low: N, high: N, tolerance: N, secret: N) -> NumericGame<N> {
+        assert!(low <= high, "the range {low}..={high} is empty");
+
+        NumericGame {
+            secret,
+            low,
+            high,
+            tolerance,
+            progress: Progress::new(config),
+            history: Vec::new(),
+        }
+    }
+
+    /// Same seed, same range, same secret.
+    pub fn seeded(config: &Config, low: N, high: N, tolerance: N, seed: u64) -> NumericGame<N> {
+        let secret = StdRng::seed_from_u64(seed).gen_range(low..=high);
+        NumericGame::new(config, low, high, tolerance, secret)
+    }
+
+    /// Times the game against `clock` instead of the system's.
+    pub fn with_clock(self, clock: Box<dyn Clock>) -> NumericGame<N> {
+        NumericGame {
+            progress: self.progress.with_clock(clock),
+            ..self
+        }
+    }
+
+    pub fn guess(&mut self, guess: N) -> Outcome {
+        let outcome = outcome(guess, self.secret, self.tolerance);
+
+        self.history.push(Turn::Guess(guess, outcome));
+        self.progress.guessed(outcome == Outcome::Win);
+
+        outcome
+    }
+
+    /// Like [`crate::Game::submit`]: checks the time limits, then parses and range checks the
+    /// line before guessing it.
+    pub fn submit(&mut self, line: &str) -> Result<Outcome, Rejection> {
+        let result = self
+            .progress
+            .start_turn()
+            .and_then(|()| self.parse(line.trim()));
+
+        match result {
+            Ok(guess) => Ok(self.guess(guess)),
+            Err(rejection) => {
+                self.history
+                    .push(Turn::Rejected(line.trim().to_string(), rejection.clone()));
+                self.progress.rejected();
+                Err(rejection)
+            }
+        }
+    }
+
+    fn parse(&self, text: &str) -> Result<N, Rejection> {
+        let guess = N::parse(text)?;
+
+        match (self.low..=self.high).contains(&guess) {
+            true => Ok(guess),
+            false => Err(Rejection::Outside {
+                guess: text.to_string(),
+                low: self.low.to_string(),
+                high: self.high.to_string(),
+            }),
+        }
+    }
+
+    pub fn secret(&self) -> N {
+        self.secret
+    }
+
+    pub fn history(&self) -> &[Turn<N>] {
+        &self.history
+    }
+}
+
+impl<N: Number> Round for NumericGame<N> {
+    fn kind(&self) -> GameKind {
+        GameKind::Numbers
+    }
+
+    fn answer(&mut self, line: &str) -> Result<Answer, Rejection> {
+        self.submit(line).map(Answer::Number)
+    }
+
+    // with a tolerance the guess that won isn't necessarily the secret, so say what it was
+    fn feedback(&self, answer: &Answer, messages: &Messages) -> String {
+        let feedback = messages.answer(answer);
+
+        match *answer == Answer::Number(Outcome::Win) && self.tolerance != N::zero() {
+            true => format!(
+                "{feedback}\n{}",
+                messages.format(Key::SecretWas, &[("secret", &self.secret)])
+            ),
+            false => feedback,
+        }
+    }
+
+    fn prompt(&self, messages: &Messages) -> String {
+        messages.between(&self.low, &self.high)
+    }
+
+    fn secret(&self) -> Secret {
+        Secret::Code(self.secret.to_string())
+    }
+
+    fn possibilities(&self) -> f64 {
+        N::possibilities(self.low, self.high, self.tolerance)
+    }
+
+    fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    fn progress_mut(&mut self) -> &mut Progress {
+        &mut self.progress
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+
+    fn setup(kind: &str, min: &str, max: &str) -> Setup {
+        Setup::new(kind, Some(min.into()), Some(max.into()), None).unwrap()
+    }
+
+    fn game(setup: &Setup) -> Result<Box<dyn Round>, ConfigError> {
+        setup.game(&Config::default(), 1, Box::new(SystemClock::new()))
+    }
+
+    #[test]
+    fn a_decimal_range_too_wide_to_pick_from_is_an_error() {
+        let wide = setup("decimal", "-1e308", "1e308");
+        assert_eq!(
+            game(&wide).err(),
+            Some(ConfigError::TooWide {
+                low: String::from("-1e308"),
+                high: String::from("1e308"),
+            })
+        );
+
+        assert!(game(&setup("decimal", "-1e307", "1e307")).is_ok());
+    }
+
+    #[test]
+    fn the_widest_integer_ranges_are_fine() {
+        let i128 = setup("i128", &i128::MIN.to_string(), &i128::MAX.to_string());
+        let u64 = setup("u64", "0", &u64::MAX.to_string());
+
+        assert!(game(&i128).is_ok());
+        assert!(game(&u64).is_ok());
+    }
+}
@@ -0,0 +1,269 @@
+//! What every guessing game has in common, so the terminal loop and transcripts don't have to
+//! care which one is being played.
+use crate::clock::{Clock, SystemClock, Timeout, Timer};
+use crate::config::Config;
+use crate::game::Outcome;
+use crate::input::Rejection;
+use crate::messages::Messages;
+use crate::score;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// The answer to one guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(Outcome),
+    Code {
+        bulls: u32,
+        cows: u32,
+    },
+    /// With several secrets, the answer for the nearest one still to find. A win means that one's
+    /// found, not that the game is.
+    Nearest(Outcome),
+    /// With several secrets, the answer for each of them, smallest secret first.
+    Each(Vec<Outcome>),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().answer(self))
+    }
+}
+
+/// Whatever is being guessed at. Serialises as a plain number or string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Secret {
+    Number(i64),
+    /// Anything that isn't an `i64`: a code, which keeps its leading zeros, or a number from a
+    /// game over a wider type.
+    Code(String),
+    /// All of them, when there are several.
+    Many(Vec<i64>),
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Secret::Number(n) => write!(f, "{n}"),
+            Secret::Code(code) => write!(f, "{code}"),
+            Secret::Many(secrets) => {
+                let secrets: Vec<String> = secrets.iter().map(i64::to_string).collect();
+                write!(f, "{}", secrets.join(", "))
+            }
+        }
+    }
+}
+
+/// Which game a transcript is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameKind {
+    #[default]
+    Number,
+    Bulls,
+    /// Several secrets, answered for the nearest one still to find.
+    FindAll,
+    /// Several secrets, answered for every one of them.
+    FindEach,
+    /// The number game over some other kind of number, see [`crate::number`].
+    Numbers,
+}
+
+/// A game that's played a line of input at a time until it's won or the attempts run out.
+pub trait Round {
+    fn kind(&self) -> GameKind;
+
+    /// Plays a raw line of input, rejecting anything that isn't a guess.
+    fn answer(&mut self, line: &str) -> Result<Answer, Rejection>;
+
+    /// What to tell the player about an answer. Just the answer unless the game has more to say.
+    fn feedback(&self, answer: &Answer, messages: &Messages) -> String {
+        messages.answer(answer)
+    }
+
+    /// Goes after "Please input your guess", e.g. "between 1 and 100".
+    fn prompt(&self, messages: &Messages) -> String;
+
+    fn secret(&self) -> Secret;
+
+    /// How many secrets there could have been, which is what a win is scored on.
+    fn possibilities(&self) -> f64;
+
+    /// Points for the game, see [`score::points`]. Nothing unless it's been won.
+    fn score(&self) -> u32 {
+        match self.is_won() {
+            true => score::points(self.attempts(), self.possibilities()),
+            false => 0,
+        }
+    }
+
+    /// The attempts and time limits, which every game keeps the same way.
+    fn progress(&self) -> &Progress;
+
+    fn progress_mut(&mut self) -> &mut Progress;
+
+    /// Positions in the history where the host lied, for games that allow it.
+    fn lies(&self) -> &[usize] {
+        &[]
+    }
+
+    fn attempts(&self) -> u32 {
+        self.progress().attempts()
+    }
+
+    /// `None` when there's no limit.
+    fn attempts_left(&self) -> Option<u32> {
+        self.progress().attempts_left()
+    }
+
+    fn is_won(&self) -> bool {
+        self.progress().is_won()
+    }
+
+    /// Out of guesses, or out of time, without having found the secret.
+    fn is_lost(&self) -> bool {
+        self.progress().is_lost()
+    }
+
+    fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    /// How long until a time limit runs out, if there are any.
+    fn time_left(&self) -> Option<Duration> {
+        self.progress().time_left()
+    }
+
+    /// Ends the game if it's run out of time, and says which limit it was.
+    fn check_time(&mut self) -> Option<Timeout> {
+        self.progress_mut().check_time()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.progress().elapsed()
+    }
+}
+
+/// The bookkeeping every game does the same way: attempts against the limit, whether it's been
+/// won, and the time limits. Games keep one of these next to whatever they're guessing at.
+#[derive(Debug)]
+pub struct Progress {
+    max_attempts: Option<u32>,
+    count_rejected: bool,
+    timer: Timer,
+    timed_out: Option<Timeout>,
+    attempts: u32,
+    won: bool,
+}
+
+impl Progress {
+    /// Takes the limits from `config` and starts the clock.
+    pub fn new(config: &Config) -> Progress {
+        Progress {
+            max_attempts: config.max_attempts,
+            count_rejected: config.count_rejected,
+            timer: Timer::new(
+                Box::new(SystemClock::new()),
+                config.guess_time,
+                config.game_time,
+            ),
+            timed_out: None,
+            attempts: 0,
+            won: false,
+        }
+    }
+
+    /// Times the game against `clock` instead of the system's, restarting the timer.
+    pub fn with_clock(self, clock: Box<dyn Clock>) -> Progress {
+        let (per_guess, per_game) = self.timer.limits();
+
+        Progress {
+            timer: Timer::new(clock, per_guess, per_game),
+            ..self
+        }
+    }
+
+    /// Goes before playing a line of input: a line that arrives after the time limit is
+    /// rejected and loses the game. Either way the clock for the next guess starts now.
+    pub fn start_turn(&mut self) -> Result<(), Rejection> {
+        let timeout = self.check_time();
+        self.timer.restart_guess();
+
+        match timeout {
+            Some(timeout) => Err(Rejection::TimedOut(timeout)),
+            None => Ok(()),
+        }
+    }
+
+    /// A guess was played.
+    pub fn guessed(&mut self, won: bool) {
+        self.attempts += 1;
+        self.won = won;
+        self.stop_if_over();
+    }
+
+    /// A line wasn't a guess, which only costs an attempt if `count_rejected` is set.
+    pub fn rejected(&mut self) {
+        if self.count_rejected {
+            self.attempts += 1;
+        }
+        self.stop_if_over();
+    }
+
+    fn stop_if_over(&mut self) {
+        if self.is_over() {
+            self.timer.stop();
+        }
+    }
+
+    /// Loses the game if a time limit has run out. Front-ends waiting on input call this when
+    /// [`Progress::time_left`] is up.
+    pub fn check_time(&mut self) -> Option<Timeout> {
+        if self.timed_out.is_none() && !self.is_over() {
+            self.timed_out = self.timer.expired();
+            if self.timed_out.is_some() {
+                self.timer.stop();
+            }
+        }
+
+        self.timed_out
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// `None` when there's no limit.
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.max_attempts
+            .map(|max| max.saturating_sub(self.attempts))
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    /// Out of guesses or out of time without having found the secret.
+    pub fn is_lost(&self) -> bool {
+        !self.won && (self.attempts_left() == Some(0) || self.timed_out.is_some())
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    /// How long until a time limit runs out, if there are any.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.timer.time_left()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
+    pub fn timed_out(&self) -> Option<Timeout> {
+        self.timed_out
+    }
+}
@@ -0,0 +1,283 @@
+use crate::config::Range;
+use std::cmp::Ordering;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Points for a win: how many bits the range was worth, spread over the guesses it took.
+///
+/// Playing a perfect binary search lands close to 100 on any range, guessing right first time on
+/// `1..=100` is worth 664.
+pub fn score(attempts: u32, range: Range) -> u32 {
+    points(attempts, range.size() as f64)
+}
+
+/// [`score`] for any game, given how many secrets there could have been. Every game kind is
+/// scored in bits like this, so a code cracked quickly is worth about what a number is.
+pub fn points(attempts: u32, possibilities: f64) -> u32 {
+    if attempts == 0 {
+        return 0;
+    }
+
+    let bits = possibilities.max(1.0).log2();
+    (100.0 * bits / attempts as f64).round() as u32
+}
+
+/// Speed-run tables are filed under the difficulty with this on the end, and ranked by time.
+pub const SPEEDRUN: &str = " speedrun";
+
+/// The best game a player has had on one difficulty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScore {
+    pub player: String,
+    pub difficulty: String,
+    pub score: u32,
+    pub attempts: u32,
+    /// How long the game took, in milliseconds.
+    pub millis: u64,
+}
+
+impl HighScore {
+    // one line per entry: player, difficulty, score, attempts, millis, separated by tabs. Files
+    // from before there were speed runs don't have millis.
+    fn parse(line: &str) -> Option<HighScore> {
+        let mut fields = line.split('\t');
+
+        let high_score = HighScore {
+            player: fields.next().filter(|f| !f.is_empty())?.to_string(),
+            difficulty: fields.next().filter(|f| !f.is_empty())?.to_string(),
+            score: fields.next()?.parse().ok()?,
+            attempts: fields.next()?.parse().ok()?,
+            millis: match fields.next() {
+                Some(millis) => millis.parse().ok()?,
+                None => 0,
+            },
+        };
+
+        match fields.next() {
+            Some(_) => None,
+            None => Some(high_score),
+        }
+    }
+
+    fn is_speedrun(&self) -> bool {
+        self.difficulty.ends_with(SPEEDRUN)
+    }
+
+    /// Best first: the highest score, or on a speed-run table the quickest time.
+    fn rank(&self, other: &HighScore) -> Ordering {
+        match self.is_speedrun() {
+            true => self.millis.cmp(&other.millis),
+            false => other.score.cmp(&self.score),
+        }
+        .then(self.attempts.cmp(&other.attempts))
+    }
+}
+
+/// The high-score file: one best score per player per difficulty.
+#[derive(Debug, Default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+    /// Lines that couldn't be read when the file was loaded.
+    pub skipped: usize,
+}
+
+impl HighScores {
+    /// `$HOME/.guessing_game_scores`, or the current directory if there's no home.
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        home.join(".guessing_game_scores")
+    }
+
+    /// Reads the table, treating a missing file as an empty one.
+    ///
+    /// A half-written or hand-edited file shouldn't cost everyone their scores, so lines that
+    /// don't parse are skipped (and counted) rather than failing the whole load.
+    pub fn load(path: &Path) -> io::Result<HighScores> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HighScores::default()),
+            Err(err) => return Err(err),
+        };
+
+        let mut scores = HighScores::default();
+        for line in String::from_utf8_lossy(&bytes).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match HighScore::parse(line) {
+                Some(entry) => {
+                    scores.record(entry);
+                }
+                None => scores.skipped += 1,
+            }
+        }
+
+        Ok(scores)
+    }
+
+    /// Writes to a temporary file first and renames it over the old one, so a crash half way
+    /// through leaves the previous table intact.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::new();
+        for entry in &self.entries {
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                entry.player, entry.difficulty, entry.score, entry.attempts, entry.millis
+            ));
+        }
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Keeps the score if it beats the player's best on this difficulty. Returns whether it did.
+    pub fn record(&mut self, mut entry: HighScore) -> bool {
+        // tabs and newlines would break the file format
+        entry.player = clean(&entry.player);
+        entry.difficulty = clean(&entry.difficulty);
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.player == entry.player && e.difficulty == entry.difficulty)
+        {
+            Some(best) if best.rank(&entry) != Ordering::Greater => false,
+            Some(best) => {
+                *best = entry;
+                true
+            }
+            None => {
+                self.entries.push(entry);
+                true
+            }
+        }
+    }
+
+    pub fn best(&self, player: &str, difficulty: &str) -> Option<&HighScore> {
+        self.entries
+            .iter()
+            .find(|e| e.player == clean(player) && e.difficulty == clean(difficulty))
+    }
+
+    /// The best players on a difficulty, best first.
+    pub fn top(&self, difficulty: &str, n: usize) -> Vec<&HighScore> {
+        let mut top: Vec<&HighScore> = self
+            .entries
+            .iter()
+            .filter(|e| e.difficulty == clean(difficulty))
+            .collect();
+
+        top.sort_by(|a, b| a.rank(b));
+        top.truncate(n);
+        top
+    }
+}
+
+/// Tabs and newlines would break the files, so they're turned into spaces.
+pub(crate) fn clean(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(player: &str, difficulty: &str, score: u32, attempts: u32, millis: u64) -> HighScore {
+        HighScore {
+            player: player.to_string(),
+            difficulty: difficulty.to_string(),
+            score,
+            attempts,
+            millis,
+        }
+    }
+
+    #[test]
+    fn a_line_is_five_fields_or_four_from_before_speed_runs() {
+        assert_eq!(
+            HighScore::parse("ana\tnormal\t120\t6\t3400"),
+            Some(entry("ana", "normal", 120, 6, 3400))
+        );
+        assert_eq!(
+            HighScore::parse("ana\tnormal\t120\t6"),
+            Some(entry("ana", "normal", 120, 6, 0))
+        );
+
+        assert_eq!(HighScore::parse("ana\tnormal\t120"), None);
+        assert_eq!(HighScore::parse("ana\tnormal\tlots\t6"), None);
+        assert_eq!(HighScore::parse("\tnormal\t120\t6"), None);
+        assert_eq!(HighScore::parse("ana\tnormal\t120\t6\t3400\textra"), None);
+    }
+
+    #[test]
+    fn lines_that_dont_parse_are_skipped() {
+        let path = std::env::temp_dir().join(format!("scores_skipped_{}", std::process::id()));
+        fs::write(
+            &path,
+            "ana\tnormal\t120\t6\t3400\nnonsense\n\nbo\thard\t90\t9\nana\teasy\t-1\t2\n",
+        )
+        .unwrap();
+
+        let scores = HighScores::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(scores.skipped, 2);
+        assert_eq!(scores.best("ana", "normal").map(|e| e.score), Some(120));
+        assert_eq!(scores.best("bo", "hard").map(|e| e.millis), Some(0));
+        assert_eq!(scores.best("ana", "easy"), None);
+    }
+
+    #[test]
+    fn only_an_improvement_is_kept() {
+        let mut scores = HighScores::default();
+
+        assert!(scores.record(entry("ana", "normal", 100, 7, 5000)));
+        assert!(!scores.record(entry("ana", "normal", 90, 5, 1000)));
+        // the same score has to take fewer attempts
+        assert!(!scores.record(entry("ana", "normal", 100, 7, 1000)));
+        assert!(scores.record(entry("ana", "normal", 100, 6, 9000)));
+        assert!(scores.record(entry("ana", "normal", 110, 8, 9000)));
+
+        assert_eq!(
+            scores.best("ana", "normal"),
+            Some(&entry("ana", "normal", 110, 8, 9000))
+        );
+        // someone else's score, or another difficulty, is a table of its own
+        assert!(scores.record(entry("bo", "normal", 10, 20, 0)));
+        assert!(scores.record(entry("ana", "hard", 10, 20, 0)));
+    }
+
+    #[test]
+    fn speed_runs_rank_by_time_and_the_rest_by_score() {
+        let speedrun = format!("normal{SPEEDRUN}");
+        let mut scores = HighScores::default();
+        for (player, score, attempts, millis) in [
+            ("slow", 200, 4, 9000),
+            ("quick", 50, 12, 2000),
+            ("quick too", 60, 10, 2000),
+        ] {
+            scores.record(entry(player, "normal", score, attempts, millis));
+            scores.record(entry(player, &speedrun, score, attempts, millis));
+        }
+
+        let players = |difficulty: &str| -> Vec<String> {
+            scores
+                .top(difficulty, 5)
+                .iter()
+                .map(|e| e.player.clone())
+                .collect()
+        };
+        assert_eq!(players("normal"), ["slow", "quick too", "quick"]);
+        // a tie on time goes to fewer attempts
+        assert_eq!(players(&speedrun), ["quick too", "quick", "slow"]);
+
+        // and a speed run only improves by being quicker
+        assert!(!scores.record(entry("slow", &speedrun, 900, 1, 9500)));
+        assert!(scores.record(entry("slow", &speedrun, 1, 30, 1000)));
+    }
+}
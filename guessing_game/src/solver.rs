@@ -0,0 +1,423 @@
+use crate::config::{Config, Range};
+use crate::game::{Game, Outcome};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// How a bot picks its next guess, given everything the answers so far have ruled out.
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// `low..=high` is every number still consistent with the feedback, never empty. Against a
+    /// host that lies it's only a best guess, see [`LiarAware`] for a strategy that copes.
+    fn next_guess(&mut self, low: i64, high: i64) -> i64;
+
+    /// Called before the first guess of each game.
+    fn start(&mut self, _range: Range) {}
+
+    /// Called with every answer, for strategies that keep their own notes.
+    fn observe(&mut self, _guess: i64, _outcome: Outcome) {}
+}
+
+/// Halve what's left every time.
+pub struct BinarySearch;
+
+impl Strategy for BinarySearch {
+    fn name(&self) -> &'static str {
+        "binary"
+    }
+
+    fn next_guess(&mut self, low: i64, high: i64) -> i64 {
+        midpoint(low, high)
+    }
+}
+
+/// Any number that could still be right.
+pub struct RandomGuess(StdRng);
+
+impl RandomGuess {
+    pub fn new(seed: u64) -> RandomGuess {
+        RandomGuess(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Strategy for RandomGuess {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn next_guess(&mut self, low: i64, high: i64) -> i64 {
+        self.0.gen_range(low..=high)
+    }
+}
+
+/// Counts up from the bottom, the worst sensible player.
+pub struct Linear;
+
+impl Strategy for Linear {
+    fn name(&self) -> &'static str {
+        "linear"
+    }
+
+    fn next_guess(&mut self, low: i64, _high: i64) -> i64 {
+        low
+    }
+}
+
+/// Aims for the middle but misses by up to a quarter of the gap either way, which is roughly
+/// what people do when they're "bisecting" in their heads.
+pub struct HumanLike(StdRng);
+
+impl HumanLike {
+    pub fn new(seed: u64) -> HumanLike {
+        HumanLike(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Strategy for HumanLike {
+    fn name(&self) -> &'static str {
+        "human"
+    }
+
+    fn next_guess(&mut self, low: i64, high: i64) -> i64 {
+        let middle = midpoint(low, high) as i128;
+        let wobble = (high as i128 - low as i128) / 4;
+        let guess = middle + self.0.gen_range(-wobble..=wobble);
+
+        guess.clamp(low as i128, high as i128) as i64
+    }
+}
+
+/// Ulam's searching game: the host may lie up to `lies` times, so no answer rules anything out
+/// for certain. Every number is kept with a count of how many answers it contradicts, and dropped
+/// once that's more than the host is allowed to lie.
+///
+/// Each guess is picked so that, whichever way the host answers, the largest possible Berlekamp
+/// volume is left as small as it can be. The volume of a number with `e` contradictions and `q`
+/// questions to go is `C(q, 0) + ... + C(q, lies - e)`, the number of ways the host could still
+/// answer about it. Minimising it is the classic way of playing this game optimally, and with no
+/// lies it's just a binary search.
+pub struct LiarAware {
+    lies: u32,
+    range: Range,
+    answers: Vec<(i64, Outcome)>,
+}
+
+/// A run of numbers that all contradict the same number of answers.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    low: i64,
+    high: i64,
+    errors: u32,
+}
+
+impl Segment {
+    fn len(&self) -> f64 {
+        (self.high as i128 - self.low as i128 + 1) as f64
+    }
+
+    // how many numbers in the segment are at most / more than `at`. In `i128`, since `low - 1`
+    // doesn't fit when the segment starts at `i64::MIN`
+    fn split(&self, at: i64) -> (f64, f64) {
+        let (low, high) = (self.low as i128, self.high as i128);
+        let below = ((at as i128).clamp(low - 1, high) - low + 1) as f64;
+        (below, self.len() - below)
+    }
+}
+
+impl LiarAware {
+    pub fn new(lies: u32) -> LiarAware {
+        LiarAware {
+            lies,
+            range: Range::default(),
+            answers: Vec::new(),
+        }
+    }
+
+    // whether `outcome` would have been a lie about `secret`
+    fn contradicts(secret: i64, guess: i64, outcome: Outcome) -> bool {
+        match outcome {
+            Outcome::TooSmall => secret <= guess,
+            Outcome::TooBig => secret >= guess,
+            Outcome::Win => secret != guess,
+        }
+    }
+
+    /// Every number that could still be the secret. A number that's been guessed is never one of
+    /// them, the host doesn't lie about a win.
+    fn segments(&self) -> Vec<Segment> {
+        let mut guessed: Vec<i64> = self.answers.iter().map(|(guess, _)| *guess).collect();
+        guessed.sort_unstable();
+        guessed.dedup();
+
+        let mut segments = Vec::new();
+        let mut low = self.range.low() as i128;
+        let ends = guessed
+            .iter()
+            .map(|&g| g as i128)
+            .filter(|&g| self.range.contains(g as i64))
+            .chain([self.range.high() as i128 + 1]);
+
+        for end in ends {
+            if low < end {
+                let (low, high) = (low as i64, (end - 1) as i64);
+                let errors = self
+                    .answers
+                    .iter()
+                    .filter(|(guess, outcome)| LiarAware::contradicts(low, *guess, *outcome))
+                    .count() as u32;
+
+                if errors <= self.lies {
+                    segments.push(Segment { low, high, errors });
+                }
+            }
+            low = end + 1;
+        }
+
+        segments
+    }
+
+    // C(q, 0) + ... + C(q, lies - errors), in floating point since it gets big
+    fn volume(&self, errors: u32, questions: u32) -> f64 {
+        let mut total = 0.0;
+        let mut choose = 1.0;
+
+        for i in 0..=self.lies.saturating_sub(errors) {
+            if errors > self.lies || i > questions {
+                break;
+            }
+            total += choose;
+            choose = choose * (questions - i) as f64 / (i + 1) as f64;
+        }
+
+        total
+    }
+
+    /// The volume left if the host says "too small" and if it says "too big" to `guess`.
+    fn volumes(&self, segments: &[Segment], guess: i64, questions: u32) -> (f64, f64) {
+        let mut too_small = 0.0;
+        let mut too_big = 0.0;
+
+        for segment in segments {
+            let (below, above) = segment.split(guess);
+            let (below, above) = match (segment.low..=segment.high).contains(&guess) {
+                // the guess itself is ruled out either way
+                true => (below - 1.0, above),
+                false => (below, above),
+            };
+            let truthful = self.volume(segment.errors, questions);
+            let lied = self.volume(segment.errors + 1, questions);
+
+            // "too small" means the secret is above the guess, so everything at or below it
+            // would need one more lie
+            too_small += below * lied + above * truthful;
+            too_big += below * truthful + above * lied;
+        }
+
+        (too_small, too_big)
+    }
+}
+
+impl Strategy for LiarAware {
+    fn name(&self) -> &'static str {
+        "liar"
+    }
+
+    fn start(&mut self, range: Range) {
+        self.range = range;
+        self.answers.clear();
+    }
+
+    fn observe(&mut self, guess: i64, outcome: Outcome) {
+        self.answers.push((guess, outcome));
+    }
+
+    fn next_guess(&mut self, _low: i64, _high: i64) -> i64 {
+        let segments = self.segments();
+        let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+            // the host lied more than it said it would
+            return self.range.low();
+        };
+
+        let candidates: f64 = segments.iter().map(Segment::len).sum();
+        if candidates <= 1.0 {
+            return first.low;
+        }
+
+        // roughly how many questions are left, bisecting plus a couple per lie
+        let questions = candidates.log2().ceil() as u32 + 2 * self.lies;
+        let worst = |guess: i64| {
+            let (too_small, too_big) = self.volumes(&segments, guess, questions);
+            too_small.max(too_big)
+        };
+
+        // "too small" leaves less the higher the guess, "too big" more, so find where they cross
+        let (mut low, mut high) = (first.low, last.high);
+        while low < high {
+            let middle = midpoint(low, high);
+            let (too_small, too_big) = self.volumes(&segments, middle, questions);
+            if too_small > too_big {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        let guess = [low.saturating_sub(1), low]
+            .into_iter()
+            .filter(|&g| g >= first.low)
+            .min_by(|a, b| worst(*a).total_cmp(&worst(*b)))
+            .unwrap_or(low);
+
+        // don't waste a guess on a number that's already been ruled out
+        match segments.iter().any(|s| (s.low..=s.high).contains(&guess)) {
+            true => guess,
+            false => segments
+                .iter()
+                .map(|s| guess.clamp(s.low, s.high))
+                .min_by_key(|g| (*g as i128 - guess as i128).abs())
+                .unwrap_or(guess),
+        }
+    }
+}
+
+pub const STRATEGIES: [&str; 5] = ["binary", "random", "linear", "human", "liar"];
+
+/// `lies` is how many lies the host is allowed, only the liar strategy cares.
+pub fn strategy(name: &str, seed: u64, lies: u32) -> Option<Box<dyn Strategy>> {
+    match name {
+        "binary" => Some(Box::new(BinarySearch)),
+        "random" => Some(Box::new(RandomGuess::new(seed))),
+        "linear" => Some(Box::new(Linear)),
+        "human" => Some(Box::new(HumanLike::new(seed))),
+        "liar" => Some(Box::new(LiarAware::new(lies))),
+        _ => None,
+    }
+}
+
+// the obvious (low + high) / 2 overflows on big i64 ranges
+fn midpoint(low: i64, high: i64) -> i64 {
+    ((low as i128 + high as i128).div_euclid(2)) as i64
+}
+
+/// The most guesses a binary search can need on `range`, i.e. ⌈log2(size + 1)⌉.
+pub fn binary_search_bound(range: Range) -> u32 {
+    128 - range.size().leading_zeros()
+}
+
+/// Plays one game to the end, narrowing the range after every answer. Returns whether it won.
+pub fn play(game: &mut Game, strategy: &mut dyn Strategy) -> bool {
+    let range = game.range();
+    let mut low = range.low();
+    let mut high = range.high();
+
+    strategy.start(range);
+
+    while !game.is_over() {
+        let guess = strategy.next_guess(low, high);
+        let outcome = game.guess(guess);
+        strategy.observe(guess, outcome);
+
+        match outcome {
+            Outcome::TooSmall => low = guess.saturating_add(1),
+            Outcome::TooBig => high = guess.saturating_sub(1),
+            Outcome::Win => break,
+        }
+
+        // only a lie can leave nothing in range, start looking everywhere again
+        if low > high || !range.contains(low) || !range.contains(high) {
+            low = range.low();
+            high = range.high();
+        }
+    }
+
+    game.is_won()
+}
+
+/// How a strategy got on over a batch of games.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub strategy: &'static str,
+    pub games: u32,
+    pub wins: u32,
+    pub max_attempts: u32,
+    pub total_attempts: u64,
+    /// Attempts taken to win, and how many games took that many.
+    pub histogram: BTreeMap<u32, u32>,
+}
+
+impl Report {
+    pub fn mean_attempts(&self) -> f64 {
+        match self.games {
+            0 => 0.0,
+            games => self.total_attempts as f64 / games as f64,
+        }
+    }
+}
+
+/// Plays `games` games with `strategy`. Game `n` gets the same secret whichever strategy is
+/// playing, so the strategies are compared on the same games.
+pub fn run(config: &Config, strategy: &mut dyn Strategy, games: u32, seed: u64) -> Report {
+    let mut secrets = StdRng::seed_from_u64(seed);
+    let mut report = Report {
+        strategy: strategy.name(),
+        ..Report::default()
+    };
+
+    for _ in 0..games {
+        let mut game = Game::seeded(config, secrets.gen());
+        let won = play(&mut game, strategy);
+
+        report.games += 1;
+        report.total_attempts += game.attempts() as u64;
+        report.max_attempts = report.max_attempts.max(game.attempts());
+
+        if won {
+            report.wins += 1;
+            *report.histogram.entry(game.attempts()).or_insert(0) += 1;
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_search_stays_within_its_bound() {
+        let ranges = [(1, 1), (1, 2), (1, 100), (-500, 500), (i64::MIN, i64::MAX)];
+
+        for (low, high) in ranges {
+            let config = Config {
+                range: Range::new(low, high).unwrap(),
+                ..Config::default()
+            };
+            let bound = binary_search_bound(config.range);
+
+            for seed in 0..5 {
+                let report = run(&config, &mut BinarySearch, 200, seed);
+                assert_eq!(report.wins, 200);
+                assert!(
+                    report.max_attempts <= bound,
+                    "{} attempts on {low}..={high}, the bound is {bound}",
+                    report.max_attempts
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn liar_aware_copes_with_the_whole_i64_range() {
+        let config = Config {
+            range: Range::new(i64::MIN, i64::MAX).unwrap(),
+            max_attempts: Some(100),
+            lies: 1,
+            ..Config::default()
+        };
+
+        let report = run(&config, &mut LiarAware::new(1), 10, 7);
+        assert_eq!(report.wins, 10);
+    }
+}
@@ -0,0 +1,495 @@
+//! Lifetime statistics: every finished game is kept, and the numbers are worked out from them
+//! when they're asked for, so there's no running total to get out of step.
+use crate::messages::{Key, Messages};
+use crate::score::clean;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// One finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub player: String,
+    pub difficulty: String,
+    pub won: bool,
+    pub attempts: u32,
+    /// 0 for a loss.
+    pub score: u32,
+    pub millis: u64,
+}
+
+impl GameRecord {
+    // player, difficulty, won or lost, attempts, score, millis, separated by tabs
+    fn parse(line: &str) -> Option<GameRecord> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [player, difficulty, result, attempts, score, millis] = fields.as_slice() else {
+            return None;
+        };
+
+        if player.is_empty() || difficulty.is_empty() {
+            return None;
+        }
+
+        Some(GameRecord {
+            player: player.to_string(),
+            difficulty: difficulty.to_string(),
+            won: match *result {
+                "won" => true,
+                "lost" => false,
+                _ => return None,
+            },
+            attempts: attempts.parse().ok()?,
+            score: score.parse().ok()?,
+            millis: millis.parse().ok()?,
+        })
+    }
+
+    fn line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            clean(&self.player),
+            clean(&self.difficulty),
+            if self.won { "won" } else { "lost" },
+            self.attempts,
+            self.score,
+            self.millis
+        )
+    }
+}
+
+/// Every game ever recorded, oldest first.
+#[derive(Debug, Default)]
+pub struct History {
+    games: Vec<GameRecord>,
+    /// Lines that couldn't be read when the file was loaded.
+    pub skipped: usize,
+}
+
+impl History {
+    /// `$HOME/.guessing_game_stats`, next to the high scores.
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        home.join(".guessing_game_stats")
+    }
+
+    /// Reads the history, treating a missing file as an empty one and skipping lines that don't
+    /// parse, like [`crate::HighScores::load`].
+    pub fn load(path: &Path) -> io::Result<History> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(History::default()),
+            Err(err) => return Err(err),
+        };
+
+        let mut history = History::default();
+        for line in String::from_utf8_lossy(&bytes).lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match GameRecord::parse(line) {
+                Some(game) => history.games.push(game),
+                None => history.skipped += 1,
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// Adds a game to the end of the file. The history only ever grows, so appending is enough
+    /// and a crash can cost at most the game being written.
+    pub fn append(path: &Path, game: &GameRecord) -> io::Result<()> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(game.line().as_bytes())
+    }
+
+    pub fn games(&self) -> &[GameRecord] {
+        &self.games
+    }
+
+    /// Everyone who's played, in the order they first did.
+    pub fn players(&self) -> Vec<&str> {
+        let mut players: Vec<&str> = Vec::new();
+        for game in &self.games {
+            if !players.contains(&game.player.as_str()) {
+                players.push(&game.player);
+            }
+        }
+        players
+    }
+
+    pub fn stats(&self, player: &str) -> Stats {
+        Stats::new(
+            player,
+            self.games.iter().filter(|g| g.player == clean(player)),
+        )
+    }
+}
+
+/// The best game a player has had: the highest score, the fewest attempts if that's a tie.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Best {
+    pub difficulty: String,
+    pub score: u32,
+    pub attempts: u32,
+}
+
+/// One player's numbers over all their games.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub player: String,
+    pub played: u32,
+    pub won: u32,
+    pub best: Option<Best>,
+    /// Wins in a row up to and including the latest game.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// How many games were won in each number of attempts.
+    pub histogram: BTreeMap<u32, u32>,
+    #[serde(skip)]
+    total_attempts: u64,
+}
+
+impl Stats {
+    fn new<'a, I>(player: &str, games: I) -> Stats
+    where
+        I: IntoIterator<Item = &'a GameRecord>,
+    {
+        let mut stats = Stats {
+            player: player.to_string(),
+            played: 0,
+            won: 0,
+            best: None,
+            current_streak: 0,
+            longest_streak: 0,
+            histogram: BTreeMap::new(),
+            total_attempts: 0,
+        };
+
+        for game in games {
+            stats.played += 1;
+
+            if !game.won {
+                stats.current_streak = 0;
+                continue;
+            }
+
+            stats.won += 1;
+            stats.total_attempts += game.attempts as u64;
+            *stats.histogram.entry(game.attempts).or_insert(0) += 1;
+            stats.current_streak += 1;
+            stats.longest_streak = stats.longest_streak.max(stats.current_streak);
+
+            let better = match &stats.best {
+                Some(best) => (game.score, best.attempts) > (best.score, game.attempts),
+                None => true,
+            };
+            if better {
+                stats.best = Some(Best {
+                    difficulty: game.difficulty.clone(),
+                    score: game.score,
+                    attempts: game.attempts,
+                });
+            }
+        }
+
+        stats
+    }
+
+    /// As a fraction, 0 when nothing's been played.
+    pub fn win_rate(&self) -> f64 {
+        match self.played {
+            0 => 0.0,
+            played => self.won as f64 / played as f64,
+        }
+    }
+
+    /// Over the games that were won, losses don't have a meaningful number of attempts.
+    pub fn mean_attempts(&self) -> f64 {
+        match self.won {
+            0 => 0.0,
+            won => self.total_attempts as f64 / won as f64,
+        }
+    }
+}
+
+/// How `stats` prints what it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Renders `stats` for a terminal, a spreadsheet or a program. Only the text is translated,
+/// the other two are for reading back in.
+pub fn export(stats: &[Stats], format: Format, messages: &Messages) -> String {
+    match format {
+        Format::Text => text(stats, messages),
+        Format::Csv => csv(stats),
+        Format::Json => {
+            // the derived fields are the point of the export, so they go in alongside the rest
+            let rows: Vec<serde_json::Value> = stats
+                .iter()
+                .map(|s| {
+                    let mut row = serde_json::to_value(s).unwrap_or_default();
+                    row["win_rate"] = s.win_rate().into();
+                    row["mean_attempts"] = s.mean_attempts().into();
+                    row
+                })
+                .collect();
+            serde_json::to_string_pretty(&rows).unwrap_or_default() + "\n"
+        }
+    }
+}
+
+fn text(stats: &[Stats], messages: &Messages) -> String {
+    let mut out = String::new();
+
+    for (i, s) in stats.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+
+        let _ = writeln!(out, "{}", s.player);
+        let _ = writeln!(
+            out,
+            "  {}",
+            messages.format(
+                Key::StatsPlayed,
+                &[
+                    ("played", &s.played),
+                    ("won", &s.won),
+                    ("rate", &format!("{:.0}", s.win_rate() * 100.0)),
+                ],
+            )
+        );
+        let _ = writeln!(
+            out,
+            "  {}",
+            messages.format(
+                Key::StatsMean,
+                &[("mean", &format!("{:.2}", s.mean_attempts()))]
+            )
+        );
+        if let Some(best) = &s.best {
+            let _ = writeln!(
+                out,
+                "  {}",
+                messages.format(
+                    Key::StatsBest,
+                    &[
+                        ("score", &best.score),
+                        ("attempts", &best.attempts),
+                        ("label", &best.difficulty),
+                    ],
+                )
+            );
+        }
+        let _ = writeln!(
+            out,
+            "  {}",
+            messages.format(
+                Key::StatsStreak,
+                &[
+                    ("current", &s.current_streak),
+                    ("longest", &s.longest_streak),
+                ],
+            )
+        );
+
+        for line in histogram(&s.histogram) {
+            let _ = writeln!(out, "  {line}");
+        }
+    }
+
+    out
+}
+
+/// A line per number of attempts: the attempts, how many games took that many and a bar, scaled
+/// so the tallest is 40 wide.
+pub fn histogram(histogram: &BTreeMap<u32, u32>) -> Vec<String> {
+    let most = histogram.values().copied().max().unwrap_or(0);
+
+    histogram
+        .iter()
+        .map(|(attempts, count)| {
+            let bar = "#".repeat((*count as usize * 40).div_ceil(most as usize));
+            format!("{attempts:>5} {count:>6} {bar}")
+        })
+        .collect()
+}
+
+// one row per player, the histogram squeezed into a single `attempts:count` column
+fn csv(stats: &[Stats]) -> String {
+    let mut out = String::from(
+        "player,played,won,win_rate,mean_attempts,best_score,best_attempts,best_difficulty,\
+         current_streak,longest_streak,histogram\n",
+    );
+
+    for s in stats {
+        let (score, attempts, difficulty) = match &s.best {
+            Some(best) => (
+                best.score.to_string(),
+                best.attempts.to_string(),
+                best.difficulty.clone(),
+            ),
+            None => Default::default(),
+        };
+        let histogram: Vec<String> = s
+            .histogram
+            .iter()
+            .map(|(attempts, count)| format!("{attempts}:{count}"))
+            .collect();
+
+        let _ = writeln!(
+            out,
+            "{},{},{},{:.4},{:.4},{},{},{},{},{},{}",
+            csv_field(&s.player),
+            s.played,
+            s.won,
+            s.win_rate(),
+            s.mean_attempts(),
+            score,
+            attempts,
+            csv_field(&difficulty),
+            s.current_streak,
+            s.longest_streak,
+            histogram.join(" ")
+        );
+    }
+
+    out
+}
+
+// quoted only when it has to be
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(player: &str, won: bool, attempts: u32, score: u32) -> GameRecord {
+        GameRecord {
+            player: player.to_string(),
+            difficulty: format!("{attempts} attempts, {score} points"),
+            won,
+            attempts,
+            score,
+            millis: 0,
+        }
+    }
+
+    fn history(games: Vec<GameRecord>) -> History {
+        History { games, skipped: 0 }
+    }
+
+    #[test]
+    fn streaks_are_broken_by_a_loss() {
+        let history = history(vec![
+            game("ana", true, 5, 100),
+            game("ana", true, 5, 100),
+            game("ana", true, 5, 100),
+            game("ana", false, 10, 0),
+            game("ana", true, 5, 100),
+        ]);
+
+        let stats = history.stats("ana");
+        assert_eq!(stats.longest_streak, 3);
+        assert_eq!(stats.current_streak, 1);
+
+        let stats = Stats::new("ana", &history.games[..4]);
+        assert_eq!(stats.current_streak, 0);
+    }
+
+    #[test]
+    fn the_best_game_is_the_highest_score_then_the_fewest_attempts() {
+        let history = history(vec![
+            game("ana", true, 6, 110),
+            game("ana", true, 4, 110),
+            game("ana", true, 5, 110),
+            game("ana", true, 9, 90),
+            game("ana", false, 1, 0),
+        ]);
+
+        let best = history.stats("ana").best.unwrap();
+        assert_eq!((best.score, best.attempts), (110, 4));
+    }
+
+    #[test]
+    fn win_rate_and_mean_are_over_the_right_games() {
+        let history = history(vec![
+            game("ana", true, 4, 100),
+            game("ana", false, 10, 0),
+            game("ana", true, 8, 50),
+            game("ana", false, 10, 0),
+            game("bo", true, 1, 600),
+        ]);
+
+        let stats = history.stats("ana");
+        assert_eq!((stats.played, stats.won), (4, 2));
+        assert_eq!(stats.win_rate(), 0.5);
+        // losses don't count towards the mean
+        assert_eq!(stats.mean_attempts(), 6.0);
+        assert_eq!(stats.histogram, BTreeMap::from([(4, 1), (8, 1)]));
+
+        let nobody = history.stats("cy");
+        assert_eq!((nobody.win_rate(), nobody.mean_attempts()), (0.0, 0.0));
+        assert_eq!(nobody.best, None);
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_they_have_to_be() {
+        assert_eq!(csv_field("ana"), "ana");
+        assert_eq!(csv_field("Smith, Ana"), "\"Smith, Ana\"");
+        assert_eq!(
+            csv_field("ana \"the bisector\""),
+            "\"ana \"\"the bisector\"\"\""
+        );
+
+        let history = history(vec![game("Smith, Ana", true, 4, 100)]);
+        let csv = export(
+            &[history.stats("Smith, Ana")],
+            Format::Csv,
+            &Messages::default(),
+        );
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("\"Smith, Ana\",1,1,"), "{row}");
+        assert!(row.contains(",\"4 attempts, 100 points\","), "{row}");
+    }
+
+    #[test]
+    fn a_record_survives_a_round_trip() {
+        let mut record = game("ana\tbo", true, 4, 100);
+        let line = record.line();
+        record.player = String::from("ana bo");
+
+        assert_eq!(GameRecord::parse(line.trim_end()), Some(record));
+        assert_eq!(GameRecord::parse("ana\tnormal\tdrawn\t4\t100\t0"), None);
+    }
+}
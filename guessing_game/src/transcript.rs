@@ -0,0 +1,533 @@
+use crate::bulls::BullsAndCows;
+use crate::clock::ManualClock;
+use crate::config::ConfigError;
+use crate::config::{Config, Range};
+use crate::find_all::FindAll;
+use crate::game::{Game, Outcome};
+use crate::input::Rejection;
+use crate::messages::Messages;
+use crate::number::Setup;
+use crate::round::{Answer, GameKind, Round, Secret};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One line of a transcript. A game is a `start`, a `turn` per line of input, then an `end`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Record {
+    Start {
+        #[serde(default)]
+        game: GameKind,
+        /// How long a bulls and cows code is.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        digits: Option<usize>,
+        /// How many secrets there are to find.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secrets: Option<usize>,
+        /// The type and bounds of a numbers game, whose `low` and `high` aren't used.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        numbers: Option<Setup>,
+        seed: u64,
+        low: i64,
+        high: i64,
+        max_attempts: Option<u32>,
+        count_rejected: bool,
+        #[serde(default)]
+        evil: bool,
+        #[serde(default)]
+        max_lies: u32,
+        /// Time limits in milliseconds.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        guess_time: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        game_time: Option<u64>,
+        time: u64,
+    },
+    Turn {
+        line: String,
+        guess: Option<Guess>,
+        outcome: Option<Outcome>,
+        rejected: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bulls: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cows: Option<u32>,
+        /// One outcome per secret, when a round answers for all of them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        outcomes: Option<Vec<Outcome>>,
+        time: u64,
+    },
+    End {
+        result: GameResult,
+        attempts: u32,
+        secret: Secret,
+        /// Which `turn`s (counting from 1) were lies, in liar mode.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lies: Vec<usize>,
+        time: u64,
+    },
+}
+
+/// An accepted guess, like [`Secret`]: a plain number when it fits in an `i64`, otherwise the
+/// text as it was typed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Guess {
+    Number(i64),
+    Text(String),
+}
+
+impl Guess {
+    fn parse(line: &str) -> Guess {
+        let line = line.trim();
+        match line.parse() {
+            Ok(number) => Guess::Number(number),
+            Err(_) => Guess::Text(line.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameResult {
+    Won,
+    Lost,
+    /// The input ran out before the game was over.
+    Abandoned,
+}
+
+impl GameResult {
+    pub fn of(game: &dyn Round) -> GameResult {
+        if game.is_won() {
+            GameResult::Won
+        } else if game.is_lost() {
+            GameResult::Lost
+        } else {
+            GameResult::Abandoned
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Writes a game out as JSON lines as it's played.
+pub struct Transcript<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Transcript<W> {
+    pub fn new(out: W) -> Transcript<W> {
+        Transcript { out }
+    }
+
+    /// `size` is the code length for bulls and cows, or how many secrets there are to find.
+    pub fn start(
+        &mut self,
+        config: &Config,
+        seed: u64,
+        game: GameKind,
+        size: Option<usize>,
+    ) -> io::Result<()> {
+        let (digits, secrets) = match game {
+            GameKind::Bulls => (size, None),
+            GameKind::FindAll | GameKind::FindEach => (None, size),
+            GameKind::Number | GameKind::Numbers => (None, None),
+        };
+
+        self.write(&start_record(config, seed, game, digits, secrets, None))
+    }
+
+    /// [`Transcript::start`] for a numbers game.
+    pub fn start_numbers(&mut self, config: &Config, seed: u64, setup: &Setup) -> io::Result<()> {
+        let start = start_record(
+            config,
+            seed,
+            GameKind::Numbers,
+            None,
+            None,
+            Some(setup.clone()),
+        );
+        self.write(&start)
+    }
+
+    /// `line` is the raw input, newline and all, `result` is what [`Round::answer`] made of it.
+    pub fn turn(&mut self, line: &str, result: &Result<Answer, Rejection>) -> io::Result<()> {
+        self.write(&turn_record(line, result, now()))
+    }
+
+    pub fn end(&mut self, game: &dyn Round) -> io::Result<()> {
+        self.write(&end_record(game, now()))
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+fn start_record(
+    config: &Config,
+    seed: u64,
+    game: GameKind,
+    digits: Option<usize>,
+    secrets: Option<usize>,
+    numbers: Option<Setup>,
+) -> Record {
+    Record::Start {
+        game,
+        digits,
+        secrets,
+        numbers,
+        seed,
+        low: config.range.low(),
+        high: config.range.high(),
+        max_attempts: config.max_attempts,
+        count_rejected: config.count_rejected,
+        evil: config.evil,
+        max_lies: config.lies,
+        guess_time: config.guess_time.map(|limit| limit.as_millis() as u64),
+        game_time: config.game_time.map(|limit| limit.as_millis() as u64),
+        time: now(),
+    }
+}
+
+fn end_record(game: &dyn Round, time: u64) -> Record {
+    Record::End {
+        result: GameResult::of(game),
+        attempts: game.attempts(),
+        secret: game.secret(),
+        lies: game.lies().iter().map(|i| i + 1).collect(),
+        time,
+    }
+}
+
+fn turn_record(line: &str, result: &Result<Answer, Rejection>, time: u64) -> Record {
+    let line = line.trim_end_matches(['\n', '\r']).to_string();
+
+    let (mut outcome, mut rejected, mut bulls, mut cows, mut outcomes) =
+        (None, None, None, None, None);
+    match result {
+        Ok(Answer::Number(answer)) | Ok(Answer::Nearest(answer)) => outcome = Some(*answer),
+        Ok(Answer::Each(answers)) => outcomes = Some(answers.clone()),
+        Ok(Answer::Code { bulls: b, cows: c }) => (bulls, cows) = (Some(*b), Some(*c)),
+        Err(rejection) => rejected = Some(rejection.to_string()),
+    }
+
+    Record::Turn {
+        guess: match outcome.is_some() || outcomes.is_some() {
+            true => Some(Guess::parse(&line)),
+            false => None,
+        },
+        line,
+        outcome,
+        rejected,
+        bulls,
+        cows,
+        outcomes,
+        time,
+    }
+}
+
+/// Somewhere a replay didn't do what the transcript says happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// 1-based line in the transcript.
+    pub line: usize,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().divergence(self))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub turns: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Json {
+        line: usize,
+        err: serde_json::Error,
+    },
+    /// The first record wasn't a `start`.
+    MissingStart,
+    BadRange {
+        line: usize,
+    },
+    /// A numbers game whose type or bounds can't be read.
+    BadNumbers {
+        line: usize,
+        err: ConfigError,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().replay_error(self))
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+/// Plays a transcript back through a fresh [`Game`] and checks every answer matches.
+///
+/// Timestamps aren't compared, but they do drive the game's clock, so a guess that was too slow
+/// the first time is too slow again. Everything else in a `turn` or `end` record has to come out
+/// the same.
+pub fn replay<R: BufRead>(reader: R) -> Result<ReplayReport, ReplayError> {
+    let mut report = ReplayReport::default();
+    let mut game: Option<Box<dyn Round>> = None;
+    let clock = ManualClock::new();
+    let mut started = 0;
+
+    for (i, line) in reader.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Record =
+            serde_json::from_str(&line).map_err(|err| ReplayError::Json { line: line_no, err })?;
+
+        match record {
+            Record::Start {
+                game: kind,
+                digits,
+                secrets,
+                numbers,
+                seed,
+                low,
+                high,
+                max_attempts,
+                count_rejected,
+                evil,
+                max_lies,
+                guess_time,
+                game_time,
+                time,
+            } => {
+                let config = Config {
+                    range: Range::new(low, high)
+                        .map_err(|_| ReplayError::BadRange { line: line_no })?,
+                    max_attempts,
+                    count_rejected,
+                    evil,
+                    lies: max_lies,
+                    guess_time: guess_time.map(Duration::from_millis),
+                    game_time: game_time.map(Duration::from_millis),
+                    ..Config::default()
+                };
+
+                started = time;
+                clock.set(Duration::ZERO);
+                game = Some(match kind {
+                    GameKind::Number => {
+                        Box::new(Game::seeded(&config, seed).with_clock(Box::new(clock.clone())))
+                    }
+                    GameKind::Bulls => Box::new(
+                        BullsAndCows::seeded(&config, digits.unwrap_or(4), seed)
+                            .with_clock(Box::new(clock.clone())),
+                    ),
+                    GameKind::FindAll | GameKind::FindEach => Box::new(
+                        FindAll::seeded(
+                            &config,
+                            secrets.unwrap_or(3),
+                            kind == GameKind::FindEach,
+                            seed,
+                        )
+                        .with_clock(Box::new(clock.clone())),
+                    ),
+                    GameKind::Numbers => numbers
+                        .ok_or_else(|| ConfigError::MissingValue(String::from("numbers")))
+                        .and_then(|setup| setup.game(&config, seed, Box::new(clock.clone())))
+                        .map_err(|err| ReplayError::BadNumbers { line: line_no, err })?,
+                });
+            }
+            Record::Turn { ref line, time, .. } => {
+                let game = game.as_mut().ok_or(ReplayError::MissingStart)?;
+                clock.set(Duration::from_millis(time.saturating_sub(started)));
+                let actual = turn_record(line, &game.answer(line), time);
+
+                report.turns += 1;
+                if actual != record {
+                    report.divergences.push(Divergence {
+                        line: line_no,
+                        expected: describe(&record),
+                        actual: describe(&actual),
+                    });
+                }
+            }
+            Record::End { time, .. } => {
+                let game = game.as_mut().ok_or(ReplayError::MissingStart)?;
+                // the game might have ended by running out of time while waiting for input
+                clock.set(Duration::from_millis(time.saturating_sub(started)));
+                game.check_time();
+                let actual = end_record(game.as_ref(), time);
+
+                if actual != record {
+                    report.divergences.push(Divergence {
+                        line: line_no,
+                        expected: describe(&record),
+                        actual: describe(&actual),
+                    });
+                }
+            }
+        }
+    }
+
+    match game {
+        Some(_) => Ok(report),
+        None => Err(ReplayError::MissingStart),
+    }
+}
+
+// just the parts of a record that get compared, for error messages
+fn describe(record: &Record) -> String {
+    match record {
+        Record::Start { .. } => String::from("start"),
+        Record::Turn {
+            outcome: Some(outcome),
+            ..
+        } => format!("'{outcome}'"),
+        Record::Turn {
+            rejected: Some(rejected),
+            ..
+        } => format!("rejected ('{rejected}')"),
+        Record::Turn {
+            outcomes: Some(outcomes),
+            ..
+        } => format!("'{}'", Answer::Each(outcomes.clone())),
+        Record::Turn {
+            bulls: Some(bulls),
+            cows: Some(cows),
+            ..
+        } => format!(
+            "'{}'",
+            Answer::Code {
+                bulls: *bulls,
+                cows: *cows
+            }
+        ),
+        Record::Turn { .. } => String::from("nothing"),
+        Record::End {
+            result,
+            attempts,
+            secret,
+            lies,
+            ..
+        } => format!("{result:?} in {attempts} attempts with secret {secret}, lies at {lies:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guess(line: &str) -> Option<Guess> {
+        match turn_record(line, &Ok(Answer::Number(Outcome::TooBig)), 0) {
+            Record::Turn { guess, .. } => guess,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn guesses_that_arent_i64s_are_kept_as_text() {
+        assert_eq!(guess("42\n"), Some(Guess::Number(42)));
+        assert_eq!(guess("0.5\n"), Some(Guess::Text(String::from("0.5"))));
+        assert_eq!(
+            guess("18446744073709551615\n"),
+            Some(Guess::Text(String::from("18446744073709551615")))
+        );
+
+        // the number as a number, so transcripts from before still read the same
+        assert_eq!(serde_json::to_string(&Guess::Number(42)).unwrap(), "42");
+        assert_eq!(
+            serde_json::to_string(&Guess::Text("0.5".into())).unwrap(),
+            "\"0.5\""
+        );
+    }
+
+    // a game of 1..=100, won on the third line after a miss and a rejection
+    fn transcript() -> String {
+        let config = Config::default();
+        let mut game = Game::seeded(&config, 7);
+        let secret = game.secret();
+        let miss = if secret == 50 { 51 } else { 50 };
+
+        let mut transcript = Transcript::new(Vec::new());
+        transcript
+            .start(&config, 7, GameKind::Number, None)
+            .unwrap();
+        for line in [
+            format!("{miss}\n"),
+            String::from("x\n"),
+            format!("{secret}\n"),
+        ] {
+            transcript.turn(&line, &game.answer(&line)).unwrap();
+        }
+        transcript.end(&game).unwrap();
+
+        String::from_utf8(transcript.out).unwrap()
+    }
+
+    #[test]
+    fn a_game_replays_without_diverging() {
+        let report = replay(transcript().as_bytes()).unwrap();
+
+        assert_eq!(report.turns, 3);
+        assert!(report.divergences.is_empty(), "{:?}", report.divergences);
+    }
+
+    #[test]
+    fn a_changed_outcome_is_caught_on_its_line() {
+        let transcript = transcript();
+        let flipped = match transcript.contains("\"too_big\"") {
+            true => transcript.replacen("\"too_big\"", "\"too_small\"", 1),
+            false => transcript.replacen("\"too_small\"", "\"too_big\"", 1),
+        };
+
+        let report = replay(flipped.as_bytes()).unwrap();
+        assert_eq!(report.turns, 3);
+        assert_eq!(report.divergences.len(), 1);
+        // the start record is line 1, so the first turn is line 2
+        assert_eq!(report.divergences[0].line, 2);
+    }
+
+    #[test]
+    fn a_transcript_has_to_start_with_a_start() {
+        let transcript = transcript();
+        let headless: String = transcript
+            .lines()
+            .skip(1)
+            .map(|l| format!("{l}\n"))
+            .collect();
+
+        assert!(matches!(
+            replay(headless.as_bytes()),
+            Err(ReplayError::MissingStart)
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+//! A full-screen view of the number game, redrawn after every guess with plain ANSI escapes.
+//!
+//! Only the drawing lives here, and it's a pure function of the game, so the terminal loop in
+//! `main` stays small.
+use crate::clock;
+use crate::game::{Game, Turn};
+use crate::messages::{Key, Messages};
+use std::fmt::Write;
+
+/// Switches to the terminal's alternate screen, so the game doesn't scroll the shell away.
+pub const ENTER: &str = "\x1b[?1049h";
+pub const LEAVE: &str = "\x1b[?1049l";
+/// Clears the screen and puts the cursor back in the top left, before each frame.
+pub const CLEAR: &str = "\x1b[2J\x1b[H";
+
+const BAR_WIDTH: usize = 50;
+/// How many of the latest guesses fit on screen.
+const HISTORY_LINES: usize = 10;
+
+/// One screenful: the range bar, the guesses so far and `message`, usually what the last guess
+/// got back.
+pub fn render(game: &Game, message: &str, messages: &Messages) -> String {
+    let range = game.range();
+    let (low, high) = game.feasible();
+    let mut frame = String::new();
+
+    let left = match game.attempts_left() {
+        Some(left) => messages.format(Key::AttemptsLeft, &[("left", &left)]),
+        None => messages.format(Key::Attempts, &[("attempts", &game.attempts())]),
+    };
+    let time = match game.time_left() {
+        Some(left) => messages.format(Key::TimeToGo, &[("time", &clock::clock(left))]),
+        None => clock::clock(game.elapsed()),
+    };
+    let title = messages.get(Key::GuessTheNumber);
+    let _ = writeln!(
+        frame,
+        " {title}{:>width$}   {time}",
+        left,
+        width = (BAR_WIDTH + 7).saturating_sub(title.chars().count())
+    );
+    let _ = writeln!(frame);
+
+    let _ = writeln!(
+        frame,
+        " {} [{}] {}",
+        range.low(),
+        bar(range.low(), range.high(), low, high),
+        range.high()
+    );
+    let remaining = high as i128 - low as i128 + 1;
+    let _ = writeln!(
+        frame,
+        " {}",
+        messages.format(
+            Key::StillPossible,
+            &[("low", &low), ("high", &high), ("count", &remaining)]
+        )
+    );
+    let _ = writeln!(frame);
+
+    let _ = writeln!(frame, " {}", messages.get(Key::Guesses));
+    let skip = game.history().len().saturating_sub(HISTORY_LINES);
+    for (i, turn) in game.history().iter().enumerate().skip(skip) {
+        let line = match turn {
+            Turn::Guess(guess, outcome) => {
+                format!("{guess:>8}  {}", messages.outcome(*outcome))
+            }
+            Turn::Rejected(text, _) => format!(
+                "{:>8}  {}",
+                format!("'{text}'"),
+                messages.get(Key::NotAGuess)
+            ),
+        };
+        let _ = writeln!(frame, " {:>3}. {line}", i + 1);
+    }
+    if game.history().is_empty() {
+        let _ = writeln!(frame, "      {}", messages.get(Key::NoneYet));
+    }
+
+    let _ = writeln!(frame);
+    for line in message.lines() {
+        let _ = writeln!(frame, " > {line}");
+    }
+
+    frame
+}
+
+/// `#` over the part of the range that's still possible, `.` over what's been ruled out.
+fn bar(range_low: i64, range_high: i64, low: i64, high: i64) -> String {
+    let size = range_high as i128 - range_low as i128 + 1;
+    let cell = |n: i64| ((n as i128 - range_low as i128) * BAR_WIDTH as i128 / size) as usize;
+    let (from, to) = (cell(low), cell(high).min(BAR_WIDTH - 1));
+
+    (0..BAR_WIDTH)
+        .map(|i| if (from..=to).contains(&i) { '#' } else { '.' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::round::Round;
+
+    fn game() -> Game {
+        let config = Config {
+            max_attempts: Some(7),
+            ..Config::default()
+        };
+        Game::new(&config, 42)
+    }
+
+    #[test]
+    fn the_bar_covers_what_is_still_possible() {
+        assert_eq!(bar(1, 100, 1, 100), "#".repeat(BAR_WIDTH));
+        assert_eq!(
+            bar(1, 100, 51, 100),
+            format!("{}{}", ".".repeat(25), "#".repeat(25))
+        );
+        assert_eq!(
+            bar(1, 100, 42, 42),
+            format!("{}#{}", ".".repeat(20), ".".repeat(29))
+        );
+    }
+
+    #[test]
+    fn the_bar_copes_with_the_ends_of_i64() {
+        let last = bar(i64::MIN, i64::MAX, i64::MAX, i64::MAX);
+        assert_eq!(last, format!("{}#", ".".repeat(BAR_WIDTH - 1)));
+
+        let first = bar(i64::MIN, i64::MAX, i64::MIN, i64::MIN);
+        assert_eq!(first, format!("#{}", ".".repeat(BAR_WIDTH - 1)));
+    }
+
+    #[test]
+    fn a_frame_shows_the_game_so_far() {
+        let mut game = game();
+        let frame = render(&game, "", &Messages::default());
+        assert!(frame.contains("7 left"), "{frame}");
+        assert!(frame.contains("none yet"), "{frame}");
+
+        for line in ["50", "x", "25"] {
+            let _ = game.answer(line);
+        }
+        let frame = render(&game, "Too small!\nhave another go", &Messages::default());
+
+        assert!(frame.contains("5 left"), "{frame}");
+        assert!(
+            frame.contains("still possible: 26..=49 (24 numbers)"),
+            "{frame}"
+        );
+        assert!(frame.contains("  1.       50  Too big!"), "{frame}");
+        assert!(frame.contains("  2.      'x'  (not a guess)"), "{frame}");
+        assert!(frame.contains("  3.       25  Too small!"), "{frame}");
+        assert!(
+            frame.ends_with(" > Too small!\n > have another go\n"),
+            "{frame}"
+        );
+        assert!(!frame.contains("none yet"), "{frame}");
+    }
+
+    #[test]
+    fn only_the_latest_guesses_fit() {
+        let mut game = Game::new(&Config::default(), 42);
+        for guess in 1..=12 {
+            let _ = game.answer(&guess.to_string());
+        }
+
+        let frame = render(&game, "", &Messages::default());
+        assert!(!frame.contains("  2.  "), "{frame}");
+        assert!(frame.contains("  3.        3  Too small!"), "{frame}");
+        assert!(frame.contains(" 12.       12  Too small!"), "{frame}");
+    }
+}
@@ -0,0 +1,71 @@
+// how long a string is depends on who's asking. s.len() is bytes, which is what the allocator
+// cares about, but a person counts graphemes and a terminal counts columns.
+//
+//   "é" as one char        2 bytes, 1 char,  1 grapheme, 1 column
+//   "e\u{301}"             3 bytes, 2 chars, 1 grapheme, 1 column
+//   "日本"                 6 bytes, 2 chars, 2 graphemes, 4 columns (CJK is double width)
+//   "👨‍👩‍👧" (joined by ZWJs) 18 bytes, 5 chars, 1 grapheme, 2 columns
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Length {
+    pub bytes: usize,
+    pub chars: usize,
+    pub graphemes: usize,
+    pub width: usize,
+}
+
+// all four at once. like the calculate_length examples it only borrows s
+pub fn length(s: &str) -> Length {
+    Length {
+        bytes: s.len(),
+        chars: chars(s),
+        graphemes: graphemes(s),
+        width: width(s),
+    }
+}
+
+pub fn chars(s: &str) -> usize {
+    s.chars().count()
+}
+
+// extended grapheme clusters, so a flag, an accented letter or a family emoji are one each
+pub fn graphemes(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+// columns in a terminal. wide CJK and emoji take 2, combining marks and zero width joiners take
+// none, and an emoji ZWJ sequence is as wide as the one emoji it draws
+pub fn width(s: &str) -> usize {
+    s.width()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn of(bytes: usize, chars: usize, graphemes: usize, width: usize) -> Length {
+        Length {
+            bytes,
+            chars,
+            graphemes,
+            width,
+        }
+    }
+
+    // the table at the top of the file
+    #[test]
+    fn the_table_is_right() {
+        assert_eq!(length("é"), of(2, 1, 1, 1));
+        assert_eq!(length("e\u{301}"), of(3, 2, 1, 1));
+        assert_eq!(length("日本"), of(6, 2, 2, 4));
+        assert_eq!(length("👨\u{200d}👩\u{200d}👧"), of(18, 5, 1, 2));
+    }
+
+    #[test]
+    fn ascii_is_the_same_whichever_way_its_counted() {
+        assert_eq!(length("hello"), of(5, 5, 5, 5));
+        assert_eq!(length(""), of(0, 0, 0, 0));
+    }
+}
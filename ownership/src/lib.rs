@@ -0,0 +1,5 @@
+// the string helpers from this chapter, pulled out of main so they can be tested and used from
+// other crates. main.rs still walks through each of them
+pub mod length;
+pub mod slicing;
+pub mod words;
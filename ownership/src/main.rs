@@ -1,4 +1,6 @@
 // https://doc.rust-lang.org/book/ch04-01-what-is-ownership.html
+use ownership::{length, slicing, words};
+
 fn main() {
     println!("---- STRINGS ----");
     let mut s = String::from("hello");
@@ -144,11 +146,37 @@ fn main() {
         let s1 = String::from("hello");
         let _len = calculate_length(&s1);
 
+        // &String on purpose, it's what the book passes. &str would take more than Strings
+        #[allow(clippy::ptr_arg)]
         fn calculate_length(s: &String) -> usize {
             s.len()
         }
     }
 
+    // both of those are s.len() though, which is bytes. that's only the number of characters for
+    // plain ASCII, length.rs counts chars, graphemes and terminal columns as well
+    println!("---- LENGTHS ----");
+    println!(
+        "{:<20} {:>5} {:>5} {:>9} {:>5}",
+        "", "bytes", "chars", "graphemes", "width"
+    );
+    for (name, s) in [
+        ("ascii", "hello"),
+        ("precomposed é", "h\u{e9}llo"),
+        ("combining accent", "he\u{301}llo"),
+        ("cjk", "日本語"),
+        ("flag", "🇳🇿"),
+        ("zwj family", "👨\u{200d}👩\u{200d}👧"),
+        ("skin tone", "👋🏽"),
+        ("zero width space", "a\u{200b}b"),
+    ] {
+        let len = length::length(s);
+        println!(
+            "{name:<20} {:>5} {:>5} {:>9} {:>5}  {s}",
+            len.bytes, len.chars, len.graphemes, len.width
+        );
+    }
+
     // When we borrow we can't modify within the function. trying to do that results in a
     // mutability error. Just as variables are immutable by default, so are references.
     //
@@ -197,17 +225,42 @@ fn main() {
     // we can also select the entire thing
     let _slice = &s[..];
 
-    // so a way to get the first word would be
-    #[allow(dead_code)]
-    fn first_word(s: &str) -> &str {
-        let bytes = s.as_bytes();
-
-        for (i, &item) in bytes.iter().enumerate() {
-            if item == b' ' {
-                return &s[0..i];
-            }
-        }
-
-        &s[..]
+    // all of those count bytes though, and panic if an index lands in the middle of a char.
+    // "héllo" has a 2 byte 'é', so &s[0..2] on it would panic. slicing.rs checks first and
+    // hands back an Err instead, and can count in chars or graphemes too
+    println!("---- SLICES ----");
+    let s = String::from("héllo 🇳🇿");
+    println!("{:?}", slicing::by_byte(&s, 0..3));
+    println!("{:?}", slicing::by_byte(&s, 0..2));
+    if let Err(err) = slicing::by_byte(&s, ..2) {
+        println!("{err}");
     }
+    println!("{:?}", slicing::by_char(&s, ..2));
+    println!("{:?}", slicing::by_char(&s, 6..7)); // half a flag
+    println!("{:?}", slicing::by_grapheme(&s, 6..));
+    println!("{:?}", slicing::by_grapheme(&s, 6..8));
+
+    // so a way to get the first word would be to walk the bytes until we hit a space:
+    //
+    // fn first_word(s: &str) -> &str {
+    //     let bytes = s.as_bytes();
+    //
+    //     for (i, &item) in bytes.iter().enumerate() {
+    //         if item == b' ' {
+    //             return &s[0..i];
+    //         }
+    //     }
+    //
+    //     &s[..]
+    // }
+    //
+    // but that only knows about ' '. tabs, newlines, non-breaking spaces and punctuation all end
+    // up as part of the word. words.rs does it properly, and still just hands back slices of s
+    println!("---- WORDS ----");
+    let text = String::from("\u{a0}Hello,\tworld!\nIt's 3.14 o'clock in Zürich.");
+    println!("{:?}", words::words(&text).collect::<Vec<_>>());
+    println!("first: {:?}", words::first_word(&text));
+    println!("third: {:?}", words::nth_word(&text, 2));
+    println!("last: {:?}", words::last_word(&text));
+    println!("none: {:?}", words::first_word(" ... "));
 }
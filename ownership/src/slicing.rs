@@ -0,0 +1,257 @@
+// slicing without the panics. &s[0..2] is fine on "hello" but blows up on "héllo", since byte 2
+// is in the middle of the 'é'. these check first and say what went wrong instead.
+//
+// a slice can be asked for in bytes (what &s[..] uses), chars or grapheme clusters (what people
+// would call a character, so "e\u{301}" or "🇳🇿" count as one). whichever it is, what comes back
+// borrows from s, nothing gets copied.
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Bytes,
+    Chars,
+    Graphemes,
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Bytes => "bytes",
+            Unit::Chars => "chars",
+            Unit::Graphemes => "graphemes",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceError {
+    // start comes after end
+    Backwards {
+        start: usize,
+        end: usize,
+    },
+    // the string is only len units long
+    OutOfBounds {
+        index: usize,
+        len: usize,
+        unit: Unit,
+    },
+    // a byte index inside a char, with the char boundaries either side of it
+    NotABoundary {
+        index: usize,
+        before: usize,
+        after: usize,
+    },
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SliceError::Backwards { start, end } => {
+                write!(f, "the slice starts at {start} but ends at {end}")
+            }
+            SliceError::OutOfBounds { index, len, unit } => {
+                write!(f, "{index} is past the end, the string is {len} {unit} long")
+            }
+            SliceError::NotABoundary {
+                index,
+                before,
+                after,
+            } => write!(
+                f,
+                "byte {index} is inside a character, the nearest boundaries are {before} and {after}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SliceError {}
+
+// like &s[range], but an Err instead of a panic
+pub fn by_byte(s: &str, range: impl RangeBounds<usize>) -> Result<&str, SliceError> {
+    let (start, end) = bounds(&range)?;
+    let end = end.unwrap_or(s.len());
+
+    for index in [start, end] {
+        if index > s.len() {
+            return Err(SliceError::OutOfBounds {
+                index,
+                len: s.len(),
+                unit: Unit::Bytes,
+            });
+        }
+        if !s.is_char_boundary(index) {
+            // a char is at most 4 bytes, so neither of these goes far
+            let before = (0..index)
+                .rev()
+                .find(|i| s.is_char_boundary(*i))
+                .unwrap_or(0);
+            let after = (index..s.len())
+                .find(|i| s.is_char_boundary(*i))
+                .unwrap_or(s.len());
+            return Err(SliceError::NotABoundary {
+                index,
+                before,
+                after,
+            });
+        }
+    }
+
+    Ok(&s[start..end])
+}
+
+// the range counts chars, so by_char("héllo", 0..2) is "hé"
+pub fn by_char(s: &str, range: impl RangeBounds<usize>) -> Result<&str, SliceError> {
+    slice(s, &range, Unit::Chars, || s.char_indices().map(|(i, _)| i))
+}
+
+// the range counts grapheme clusters, so a flag or an accent that's its own char stays in one
+// piece
+pub fn by_grapheme(s: &str, range: impl RangeBounds<usize>) -> Result<&str, SliceError> {
+    slice(s, &range, Unit::Graphemes, || {
+        s.grapheme_indices(true).map(|(i, _)| i)
+    })
+}
+
+// turns indexes in some unit into byte offsets. offsets() gives where each unit starts, and the
+// end of the string counts as a boundary too so a slice can run right up to it
+fn slice<'a, I>(
+    s: &'a str,
+    range: &impl RangeBounds<usize>,
+    unit: Unit,
+    offsets: impl Fn() -> I,
+) -> Result<&'a str, SliceError>
+where
+    I: Iterator<Item = usize>,
+{
+    let (start, end) = bounds(range)?;
+
+    let byte = |index: usize| {
+        offsets()
+            .chain(std::iter::once(s.len()))
+            .nth(index)
+            .ok_or_else(|| SliceError::OutOfBounds {
+                index,
+                len: offsets().count(),
+                unit,
+            })
+    };
+
+    let start = byte(start)?;
+    let end = match end {
+        Some(end) => byte(end)?,
+        None => s.len(),
+    };
+    Ok(&s[start..end])
+}
+
+// the start and end of range, end exclusive. None is an open end, which is the end of the string
+// whatever unit it's counted in
+fn bounds(range: &impl RangeBounds<usize>) -> Result<(usize, Option<usize>), SliceError> {
+    let start = match range.start_bound() {
+        Bound::Included(i) => *i,
+        Bound::Excluded(i) => i.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(i) => Some(i.saturating_add(1)),
+        Bound::Excluded(i) => Some(*i),
+        Bound::Unbounded => None,
+    };
+
+    match end {
+        Some(end) if start > end => Err(SliceError::Backwards { start, end }),
+        _ => Ok((start, end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 'é' is bytes 1..3, the flag is two 4 byte chars at 7..15 but one grapheme
+    const S: &str = "héllo 🇳🇿";
+
+    #[test]
+    fn bytes_have_to_land_on_char_boundaries() {
+        assert_eq!(by_byte(S, 0..3), Ok("hé"));
+        assert_eq!(
+            by_byte(S, 0..2),
+            Err(SliceError::NotABoundary {
+                index: 2,
+                before: 1,
+                after: 3
+            })
+        );
+        assert_eq!(
+            by_byte(S, 9..),
+            Err(SliceError::NotABoundary {
+                index: 9,
+                before: 7,
+                after: 11
+            })
+        );
+        assert_eq!(by_byte(S, 7..11), Ok("🇳"));
+    }
+
+    #[test]
+    fn past_the_end_says_how_long_it_is_in_that_unit() {
+        let past = |index, len, unit| Err(SliceError::OutOfBounds { index, len, unit });
+
+        assert_eq!(by_byte(S, 3..16), past(16, 15, Unit::Bytes));
+        assert_eq!(by_char(S, 9..), past(9, 8, Unit::Chars));
+        assert_eq!(by_grapheme(S, 6..8), past(8, 7, Unit::Graphemes));
+
+        // the very end is fine, and gives an empty slice
+        assert_eq!(by_byte(S, 15..), Ok(""));
+        assert_eq!(by_char(S, 8..8), Ok(""));
+        assert_eq!(by_grapheme(S, 7..), Ok(""));
+    }
+
+    #[test]
+    fn a_backwards_range_is_an_error_whatever_the_unit() {
+        // the kind of range that comes from arithmetic gone wrong, not a literal
+        let (start, end) = (3, 1);
+        let backwards = Err(SliceError::Backwards { start, end });
+
+        assert_eq!(by_byte(S, start..end), backwards);
+        assert_eq!(by_char(S, start..end), backwards);
+        assert_eq!(by_grapheme(S, start..=end - 1), backwards);
+    }
+
+    #[test]
+    fn open_ended_ranges_run_to_the_ends() {
+        assert_eq!(by_byte(S, ..), Ok(S));
+        assert_eq!(by_char(S, ..2), Ok("hé"));
+        assert_eq!(by_char(S, ..=1), Ok("hé"));
+        assert_eq!(by_char(S, 6..), Ok("🇳🇿"));
+        assert_eq!(by_grapheme(S, ..), Ok(S));
+    }
+
+    #[test]
+    fn graphemes_dont_split_a_flag_or_an_accent() {
+        // by char, half a flag is a lone regional indicator
+        assert_eq!(by_char(S, 6..7), Ok("🇳"));
+        assert_eq!(by_grapheme(S, 6..7), Ok("🇳🇿"));
+
+        let combining = "e\u{301}tude";
+        assert_eq!(by_char(combining, ..1), Ok("e"));
+        assert_eq!(by_grapheme(combining, ..1), Ok("e\u{301}"));
+    }
+
+    #[test]
+    fn errors_say_where_to_look_instead() {
+        let err = by_byte(S, ..2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "byte 2 is inside a character, the nearest boundaries are 1 and 3"
+        );
+        let err = by_grapheme(S, 9..).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "9 is past the end, the string is 7 graphemes long"
+        );
+    }
+}
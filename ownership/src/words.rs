@@ -0,0 +1,61 @@
+// words, the way Unicode splits them (UAX #29) rather than on b' '.
+//
+// everything here hands back slices of the string it was given, so nothing gets allocated and the
+// borrow checker ties the words to the original string, same as the byte version did.
+use unicode_segmentation::UnicodeSegmentation;
+
+// every word in s, in order. whitespace and punctuation between words are skipped, but things like
+// "can't", "3.14" or "naïve" stay in one piece
+pub fn words(s: &str) -> impl DoubleEndedIterator<Item = &str> {
+    s.unicode_words()
+}
+
+// None when there's no word at all, e.g. "" or "  ...  "
+pub fn first_word(s: &str) -> Option<&str> {
+    words(s).next()
+}
+
+// counting from 0, like everything else
+pub fn nth_word(s: &str, n: usize) -> Option<&str> {
+    words(s).nth(n)
+}
+
+// walks in from the end, so it doesn't go over the whole string first
+pub fn last_word(s: &str) -> Option<&str> {
+    words(s).next_back()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punctuation_and_spaces_arent_words() {
+        let text = "  Hello, world! It's 3.14 o'clock in Zürich.  ";
+        assert_eq!(
+            words(text).collect::<Vec<_>>(),
+            ["Hello", "world", "It's", "3.14", "o'clock", "in", "Zürich"]
+        );
+        assert_eq!(words("  ...  ").count(), 0);
+    }
+
+    #[test]
+    fn first_nth_and_last() {
+        let text = "naïve café\tau lait";
+        assert_eq!(first_word(text), Some("naïve"));
+        assert_eq!(nth_word(text, 2), Some("au"));
+        assert_eq!(nth_word(text, 4), None);
+        assert_eq!(last_word(text), Some("lait"));
+
+        assert_eq!(first_word(""), None);
+        assert_eq!(last_word(" ... "), None);
+    }
+
+    #[test]
+    fn words_borrow_from_the_string() {
+        let text = String::from("hello world");
+        let word = first_word(&text).unwrap();
+
+        assert_eq!(word.as_ptr(), text.as_ptr());
+    }
+}